@@ -8,7 +8,23 @@ from latest_version._latest_version import (
     find_executables_py as find_executables,
     get_version_py as get_version,
     find_latest_command_py as find_latest_command,
+    find_all_versions_py as find_all_versions,
+    find_latest_command_detailed_py as find_latest_command_detailed,
     PyExecutableInfo as ExecutableInfo,
+    PyLatestReport as LatestReport,
+    LatestVersionError,
+    CommandNotFoundError,
+    CommandExecutionError,
+    VersionExtractionError,
+    VersionParsingError,
+    ProbeTimeoutError,
+    __version__,
 )
 
-__version__ = "0.1.0"
\ No newline at end of file
+try:
+    # Only present when built with the `tokio` feature
+    from latest_version._latest_version import (
+        find_latest_command_async_py as find_latest_command_async,
+    )
+except ImportError:
+    pass
@@ -0,0 +1,134 @@
+use crate::{
+    find_executables, no_version_found, probe_outcome, probes_for, select_latest, split_results,
+    ExecutableInfo, LatestVersionError, ProbeOptions, ProbeOutput, SelectionOptions, Unversioned,
+};
+use futures::future::join_all;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Like [`find_latest_command`](crate::find_latest_command), but probes every
+/// executable concurrently on the tokio runtime instead of blocking a thread.
+pub async fn find_latest_command_async(
+    command: &str,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    probe_latest_async(command, find_executables(command)?).await
+}
+
+async fn probe_latest_async(
+    command: &str,
+    executables: Vec<String>,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let results = join_all(
+        executables
+            .iter()
+            .map(|executable| get_version_async(executable)),
+    )
+    .await;
+    let (info_list, _) = split_results(executables, results);
+
+    select_latest(command, info_list, &SelectionOptions::default())
+}
+
+/// Probes with the flags and timeout from [`ProbeOptions::default`], so the
+/// environment overrides apply here too.
+async fn get_version_async(executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
+    let options = ProbeOptions::default();
+    let mut unversioned = Unversioned::default();
+
+    for (index, probe) in probes_for(executable_path, &options).iter().enumerate() {
+        let output = run_probe_async(executable_path, probe, options.timeout).await;
+        if let Some(result) = probe_outcome(
+            executable_path,
+            index,
+            probe,
+            output,
+            &options.extract,
+            &mut unversioned,
+        ) {
+            return result.map(|(info, _)| info);
+        }
+    }
+
+    Err(no_version_found(executable_path, unversioned))
+}
+
+/// Run `executable_path probe...` and collect its output, killing it if it
+/// outlives `timeout`.
+async fn run_probe_async(
+    executable_path: &str,
+    probe: &[&str],
+    timeout: Option<Duration>,
+) -> std::io::Result<ProbeOutput> {
+    let child = Command::new(executable_path)
+        .args(probe)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let Some(timeout) = timeout else {
+        return Ok(ProbeOutput::from(child.wait_with_output().await?));
+    };
+
+    // Dropping the unfinished wait on timeout kills the child.
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("no exit within {:?}", timeout),
+            )
+        })??;
+
+    Ok(ProbeOutput::from(output))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::find_executables_in;
+    use crate::tests::write_script;
+
+    #[tokio::test]
+    async fn test_async_probes_every_candidate() {
+        let old_dir = tempfile::tempdir().unwrap();
+        let broken_dir = tempfile::tempdir().unwrap();
+        let new_dir = tempfile::tempdir().unwrap();
+        write_script(old_dir.path(), "tool", "echo 'tool 1.9.0'");
+        write_script(broken_dir.path(), "tool", "echo 'no version here'");
+        let newest = write_script(new_dir.path(), "tool", "echo 'tool 1.10.2'");
+
+        let search_dirs = vec![
+            old_dir.path().to_path_buf(),
+            broken_dir.path().to_path_buf(),
+            new_dir.path().to_path_buf(),
+        ];
+        let executables = find_executables_in("tool", &search_dirs).unwrap();
+
+        let latest = probe_latest_async("tool", executables).await.unwrap();
+        assert_eq!(latest.path, newest.to_str().unwrap());
+        assert_eq!(latest.version, "1.10.2");
+        assert_eq!(
+            latest.probe_command,
+            [newest.to_str().unwrap(), "--version"]
+        );
+
+        let latest = find_latest_command_async(newest.to_str().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(latest.version, "1.10.2");
+    }
+
+    #[tokio::test]
+    async fn test_async_reports_missing_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let silent = write_script(dir.path(), "silent", "echo 'no version here'");
+
+        assert!(matches!(
+            find_latest_command_async(silent.to_str().unwrap()).await,
+            Err(LatestVersionError::VersionExtractionError(_))
+        ));
+    }
+}
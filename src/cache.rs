@@ -0,0 +1,364 @@
+use crate::{get_version_with, ExecutableInfo, ExtractOptions, LatestVersionError, ProbeOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheEntry {
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    #[serde(default)]
+    size: u64,
+    version: String,
+    #[serde(default)]
+    probe_command: Vec<String>,
+}
+
+/// Probe results persisted between runs, keyed by canonical executable path and
+/// invalidated whenever the executable's modification time or size changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl VersionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Location of the cache file under the user's cache directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("latest-version").join("versions.json"))
+    }
+
+    /// Load a cache from `path`, returning an empty cache if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self, LatestVersionError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| LatestVersionError::CacheError(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(LatestVersionError::CacheError(e.to_string())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), LatestVersionError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| LatestVersionError::CacheError(e.to_string()))?;
+        }
+
+        let contents = serde_json::to_string(self)
+            .map_err(|e| LatestVersionError::CacheError(e.to_string()))?;
+
+        std::fs::write(path, contents).map_err(|e| LatestVersionError::CacheError(e.to_string()))
+    }
+
+    /// Remove the cache file at `path`, if there is one.
+    pub fn clear(path: &Path) -> Result<(), LatestVersionError> {
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(LatestVersionError::CacheError(e.to_string())),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn lookup(&self, executable_path: &str, stamp: &Stamp) -> Option<ExecutableInfo> {
+        self.entries
+            .get(&stamp.key)
+            .filter(|entry| {
+                (entry.mtime_secs, entry.mtime_nanos, entry.size)
+                    == (stamp.mtime_secs, stamp.mtime_nanos, stamp.size)
+            })
+            .map(|entry| ExecutableInfo {
+                path: executable_path.to_string(),
+                version: entry.version.clone(),
+                probe_command: entry.probe_command.clone(),
+            })
+    }
+
+    fn insert(&mut self, stamp: Stamp, info: &ExecutableInfo) {
+        self.entries.insert(
+            stamp.key,
+            CacheEntry {
+                mtime_secs: stamp.mtime_secs,
+                mtime_nanos: stamp.mtime_nanos,
+                size: stamp.size,
+                version: info.version.clone(),
+                probe_command: info.probe_command.clone(),
+            },
+        );
+    }
+}
+
+/// What identifies an executable on disk: where it really lives, and enough
+/// of its metadata to notice when it is replaced.
+struct Stamp {
+    key: String,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    size: u64,
+}
+
+fn stamp(executable_path: &str) -> Option<Stamp> {
+    // Symlinks to the same file share an entry.
+    let real_path = std::fs::canonicalize(executable_path).ok()?;
+    let metadata = std::fs::metadata(&real_path).ok()?;
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    Some(Stamp {
+        key: real_path.to_str()?.to_string(),
+        mtime_secs: since_epoch.as_secs(),
+        mtime_nanos: since_epoch.subsec_nanos(),
+        size: metadata.len(),
+    })
+}
+
+/// Like [`get_version`](crate::get_version), but reuses a cached result when the executable is unchanged.
+pub fn get_version_cached(
+    executable_path: &str,
+    cache: &mut VersionCache,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    get_version_cached_with(executable_path, cache, &ProbeOptions::default())
+}
+
+/// Whether versions probed with `options` are shared through the cache. Entries
+/// don't record how they were probed, so only the built-in probes, arguments
+/// and extraction qualify; anything else is always probed afresh.
+fn is_cacheable(options: &ProbeOptions) -> bool {
+    let default_probe = options.stdin_input.is_none()
+        && options.flags.is_empty()
+        && options.probes.is_empty()
+        && options.args.is_empty()
+        && options.extract == ExtractOptions::default();
+    #[cfg(unix)]
+    let default_probe = default_probe && options.arg0.is_none();
+    default_probe
+}
+
+/// The stamp to look `executable_path` up under, or `None` to bypass the cache.
+fn stamp_for(executable_path: &str, options: &ProbeOptions) -> Option<Stamp> {
+    is_cacheable(options)
+        .then(|| stamp(executable_path))
+        .flatten()
+}
+
+/// Like [`get_version_cached`], but probes a cache miss according to `options`.
+/// Unless those are the built-in probes, the cache is neither read nor updated.
+pub fn get_version_cached_with(
+    executable_path: &str,
+    cache: &mut VersionCache,
+    options: &ProbeOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let stamp = stamp_for(executable_path, options);
+
+    if let Some(info) = stamp
+        .as_ref()
+        .and_then(|stamp| cache.lookup(executable_path, stamp))
+    {
+        return Ok(info);
+    }
+
+    let info = get_version_with(executable_path, options)?;
+
+    if let Some(stamp) = stamp {
+        cache.insert(stamp, &info);
+    }
+
+    Ok(info)
+}
+
+/// Like [`get_version_cached_with`] for each of `executables`, probing the cache
+/// misses in parallel. Results keep the order of `executables`.
+pub(crate) fn get_versions_cached_with(
+    executables: &[String],
+    cache: &mut VersionCache,
+    options: &ProbeOptions,
+) -> Vec<Result<ExecutableInfo, LatestVersionError>> {
+    let stamps: Vec<_> = executables
+        .iter()
+        .map(|path| stamp_for(path, options))
+        .collect();
+    let mut results: Vec<_> = executables
+        .iter()
+        .zip(&stamps)
+        .map(|(path, stamp)| stamp.as_ref().and_then(|stamp| cache.lookup(path, stamp)))
+        .map(|hit| hit.map(Ok))
+        .collect();
+
+    let misses: Vec<String> = executables
+        .iter()
+        .zip(&results)
+        .filter(|(_, hit)| hit.is_none())
+        .map(|(path, _)| path.clone())
+        .collect();
+    let mut probed =
+        crate::probe_in_parallel(&misses, |executable| get_version_with(executable, options))
+            .into_iter();
+
+    for (result, stamp) in results.iter_mut().zip(stamps) {
+        if result.is_some() {
+            continue;
+        }
+        let outcome = probed.next().expect("one probe per cache miss");
+        if let (Ok(info), Some(stamp)) = (&outcome, stamp) {
+            cache.insert(stamp, info);
+        }
+        *result = Some(outcome);
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tests::write_script;
+
+    #[test]
+    fn test_cached_probe_survives_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let script = write_script(
+            dir.path(),
+            "tool",
+            &format!("echo run >> '{}'\necho 'tool 1.2.3'", log.display()),
+        );
+        let script = script.to_str().unwrap();
+        let cache_file = dir.path().join("cache").join("versions.json");
+
+        let mut cache = VersionCache::load(&cache_file).unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.2.3"
+        );
+        cache.save(&cache_file).unwrap();
+
+        let mut reloaded = VersionCache::load(&cache_file).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(
+            get_version_cached(script, &mut reloaded).unwrap().version,
+            "1.2.3"
+        );
+
+        let probes = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(probes.lines().count(), 1);
+
+        VersionCache::clear(&cache_file).unwrap();
+        assert!(!cache_file.exists());
+    }
+
+    #[test]
+    fn test_changed_executable_is_reprobed() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let probe = format!("echo run >> '{}'", log.display());
+        let script = write_script(dir.path(), "tool", &format!("{}\necho 'tool 1.2.3'", probe));
+        let link = dir.path().join("tool-link");
+        std::os::unix::fs::symlink(&script, &link).unwrap();
+        let probes = || std::fs::read_to_string(&log).unwrap().lines().count();
+
+        let mut cache = VersionCache::new();
+        let script = script.to_str().unwrap();
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.2.3"
+        );
+
+        // A symlink to the same file is a hit, reported under its own path.
+        let linked = get_version_cached(link.to_str().unwrap(), &mut cache).unwrap();
+        assert_eq!(linked.path, link.to_str().unwrap());
+        assert_eq!(linked.version, "1.2.3");
+        assert_eq!(probes(), 1);
+
+        // Replacing the executable changes its size and invalidates the entry.
+        write_script(
+            dir.path(),
+            "tool",
+            &format!("{}\necho 'tool 1.10.0'", probe),
+        );
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.10.0"
+        );
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.10.0"
+        );
+        assert_eq!(probes(), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_misses_are_probed_and_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let probe = format!("echo run >> '{}'", log.display());
+        let old = write_script(dir.path(), "old", &format!("{}\necho 'tool 1.2.3'", probe));
+        let new = write_script(dir.path(), "new", &format!("{}\necho 'tool 2.0.0'", probe));
+        let executables = vec![
+            old.to_str().unwrap().to_string(),
+            new.to_str().unwrap().to_string(),
+        ];
+        let probes = || std::fs::read_to_string(&log).unwrap().lines().count();
+
+        let mut cache = VersionCache::new();
+        get_version_cached(&executables[0], &mut cache).unwrap();
+        assert_eq!(probes(), 1);
+
+        let versions: Vec<_> =
+            get_versions_cached_with(&executables, &mut cache, &ProbeOptions::default())
+                .into_iter()
+                .map(|result| result.unwrap().version)
+                .collect();
+        assert_eq!(versions, ["1.2.3", "2.0.0"]);
+        assert_eq!(probes(), 2);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_custom_probes_bypass_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(
+            dir.path(),
+            "tool",
+            "case \"$1\" in\n  -V) echo 'tool 2.0.0' ;;\n  *) echo 'tool 1.2.3' ;;\nesac",
+        );
+        let script = script.to_str().unwrap();
+        let custom = ProbeOptions {
+            flags: vec!["-V".into()],
+            ..Default::default()
+        };
+
+        let mut cache = VersionCache::new();
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.2.3"
+        );
+        assert_eq!(
+            get_version_cached_with(script, &mut cache, &custom)
+                .unwrap()
+                .version,
+            "2.0.0"
+        );
+        let versions: Vec<_> = get_versions_cached_with(&[script.to_string()], &mut cache, &custom)
+            .into_iter()
+            .map(|result| result.unwrap().version)
+            .collect();
+        assert_eq!(versions, ["2.0.0"]);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            get_version_cached(script, &mut cache).unwrap().version,
+            "1.2.3"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    find_executables, get_version, probe_latest, ExecutableInfo, LatestVersionError,
+    SelectionOptions,
+};
+use std::path::PathBuf;
+
+/// Command names that users commonly think of as the same tool.
+pub const BUILTIN_FAMILIES: &[(&str, &[&str])] = &[
+    ("python", &["python3", "python", "py"]),
+    ("pip", &["pip3", "pip"]),
+    ("node", &["node", "nodejs"]),
+    ("perl", &["perl", "perl5"]),
+];
+
+/// A table mapping family names to the command names probed for them.
+///
+/// Starts out with [`BUILTIN_FAMILIES`] and can be extended with [`CommandFamilies::add`].
+/// A name that isn't in the table is treated as a family of one.
+#[derive(Debug, Clone)]
+pub struct CommandFamilies {
+    families: Vec<(String, Vec<String>)>,
+}
+
+impl Default for CommandFamilies {
+    fn default() -> Self {
+        Self {
+            families: BUILTIN_FAMILIES
+                .iter()
+                .map(|(family, aliases)| {
+                    (
+                        family.to_string(),
+                        aliases.iter().map(|alias| alias.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CommandFamilies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `aliases` to `family`, creating the family if it doesn't exist yet.
+    pub fn add(&mut self, family: &str, aliases: &[&str]) -> &mut Self {
+        let index = match self.families.iter().position(|(name, _)| name == family) {
+            Some(index) => index,
+            None => {
+                self.families.push((family.to_string(), Vec::new()));
+                self.families.len() - 1
+            }
+        };
+
+        let known = &mut self.families[index].1;
+        for alias in aliases {
+            if !known.iter().any(|known_alias| known_alias == alias) {
+                known.push(alias.to_string());
+            }
+        }
+
+        self
+    }
+
+    /// The command names probed for `family`.
+    pub fn aliases(&self, family: &str) -> Vec<String> {
+        self.families
+            .iter()
+            .find(|(name, _)| name == family)
+            .map(|(_, aliases)| aliases.clone())
+            .unwrap_or_else(|| vec![family.to_string()])
+    }
+
+    /// Find the latest version across every command name in `family`.
+    pub fn find_latest(&self, family: &str) -> Result<ExecutableInfo, LatestVersionError> {
+        self.find_latest_using(family, find_executables)
+    }
+
+    fn find_latest_using<F>(
+        &self,
+        family: &str,
+        find: F,
+    ) -> Result<ExecutableInfo, LatestVersionError>
+    where
+        F: Fn(&str) -> Result<Vec<String>, LatestVersionError>,
+    {
+        let mut executables = Vec::new();
+        let mut real_paths = Vec::new();
+
+        for alias in self.aliases(family) {
+            match find(&alias) {
+                Ok(found) => {
+                    // Aliases are often symlinks to one another, e.g. `python` to
+                    // `python3`; probe each file once, under its first name.
+                    for executable in found {
+                        let real_path = std::fs::canonicalize(&executable)
+                            .unwrap_or_else(|_| PathBuf::from(&executable));
+                        if !real_paths.contains(&real_path) {
+                            real_paths.push(real_path);
+                            executables.push(executable);
+                        }
+                    }
+                }
+                Err(LatestVersionError::CommandNotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if executables.is_empty() {
+            return Err(LatestVersionError::CommandNotFound(family.to_string()));
+        }
+
+        probe_latest(
+            family,
+            executables,
+            get_version,
+            &SelectionOptions::default(),
+        )
+    }
+}
+
+/// Find the latest version across the built-in aliases of `family`, e.g. `python`
+/// covers `python3`, `python` and `py`.
+pub fn find_latest_family(family: &str) -> Result<ExecutableInfo, LatestVersionError> {
+    CommandFamilies::default().find_latest(family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_family_is_its_own_alias() {
+        let families = CommandFamilies::new();
+        assert_eq!(families.aliases("rustc"), vec!["rustc".to_string()]);
+    }
+
+    #[test]
+    fn test_user_extension_appends_aliases() {
+        let mut families = CommandFamilies::new();
+        families
+            .add("python", &["python3", "pypy3"])
+            .add("rust", &["rustc"]);
+
+        assert_eq!(
+            families.aliases("python"),
+            ["python3", "python", "py", "pypy3"]
+        );
+        assert_eq!(families.aliases("rust"), ["rustc"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_family_finds_newest_across_aliases() {
+        use crate::find_executables_in;
+        use crate::tests::write_script;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_script(dir.path(), "python", "echo 'Python 2.7.18'");
+        let python3 = write_script(dir.path(), "python3", "echo 'Python 3.11.4'");
+
+        let search_dirs = vec![dir.path().to_path_buf()];
+        let latest = CommandFamilies::new()
+            .find_latest_using("python", |alias| find_executables_in(alias, &search_dirs))
+            .unwrap();
+
+        assert_eq!(latest.path, python3.to_str().unwrap());
+        assert_eq!(latest.version, "3.11.4");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_family_probes_symlinked_aliases_once() {
+        use crate::find_executables_in;
+        use crate::tests::write_script;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let python3 = write_script(
+            dir.path(),
+            "python3",
+            &format!("echo run >> '{}'\necho 'Python 3.11.4'", log.display()),
+        );
+        std::os::unix::fs::symlink(&python3, dir.path().join("python")).unwrap();
+
+        let search_dirs = vec![dir.path().to_path_buf()];
+        let latest = CommandFamilies::new()
+            .find_latest_using("python", |alias| find_executables_in(alias, &search_dirs))
+            .unwrap();
+
+        assert_eq!(latest.path, python3.to_str().unwrap());
+        let probes = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(probes.lines().count(), 1);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::{
+    find_executables_in, find_executables_matching_in, find_executables_with, get_version_with,
+    probe_executables, select_latest, with_subdirs, without_dirs, ExecutableInfo,
+    LatestVersionError, NormalizePolicy, ProbeOptions, SelectionOptions, VersionScheme,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Everything that controls a lookup, configured in one place.
+///
+/// ```no_run
+/// use latest_version::{VersionFinder, VersionScheme};
+/// use std::time::Duration;
+///
+/// let latest = VersionFinder::new()
+///     .flags(["-V"])
+///     .timeout(Duration::from_secs(2))
+///     .scheme(VersionScheme::Semver)
+///     .exclude_dir("/opt/ci/shims")
+///     .latest("python3")?;
+/// # Ok::<(), latest_version::LatestVersionError>(())
+/// ```
+///
+/// [`find_latest_command`](crate::find_latest_command) and the other free
+/// functions are shorthands for a finder with some of these set.
+#[derive(Debug, Clone, Default)]
+pub struct VersionFinder {
+    probe: ProbeOptions,
+    selection: SelectionOptions,
+    search_path: Option<Vec<PathBuf>>,
+}
+
+impl VersionFinder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn from_options(probe: &ProbeOptions, selection: &SelectionOptions) -> Self {
+        Self {
+            probe: probe.clone(),
+            selection: selection.clone(),
+            search_path: None,
+        }
+    }
+
+    /// Version flags to try, in order, instead of the built-in guesses.
+    pub fn flags<I, S>(&mut self, flags: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.probe.flags = flags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Arguments to pass before the version flag, e.g. `["-m", "pip"]`.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.probe.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Also try `args` as a single probe, after any added before it and
+    /// instead of the flags, e.g. `["version", "--short"]`.
+    pub fn probe<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.probe
+            .probes
+            .push(args.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Run each executable as `arg0` instead of its path, e.g. `wget` to probe
+    /// that applet of busybox.
+    #[cfg(unix)]
+    pub fn arg0(&mut self, arg0: impl Into<String>) -> &mut Self {
+        self.probe.arg0 = Some(arg0.into());
+        self
+    }
+
+    /// Kill each probe that runs for longer than `timeout`.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.probe.timeout = Some(timeout);
+        self
+    }
+
+    /// Which kind of version to look for in the probe output.
+    pub fn scheme(&mut self, scheme: VersionScheme) -> &mut Self {
+        self.probe.extract.scheme = scheme;
+        self
+    }
+
+    /// Take the version from the group named `version` in `pattern` instead;
+    /// see [`extract_version_with_regex`](crate::extract_version_with_regex).
+    pub fn regex(&mut self, pattern: impl Into<String>) -> &mut Self {
+        self.probe.extract.regex = Some(pattern.into());
+        self
+    }
+
+    /// Leave `dir`, or anything symlinked to it, out of the search.
+    pub fn exclude_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.probe.exclude_dirs.push(dir.into());
+        self
+    }
+
+    /// Also search up to `depth` levels of subdirectories below each search
+    /// directory; see [`find_executables_recursive`](crate::find_executables_recursive).
+    pub fn recursive(&mut self, depth: usize) -> &mut Self {
+        self.probe.search_depth = depth;
+        self
+    }
+
+    /// Among executables with the same version, prefer the one in `dir` over
+    /// any in directories added after it, and over those in none of them.
+    pub fn prefer_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.selection.prefer_dirs.push(dir.into());
+        self
+    }
+
+    /// Treat the command as a pattern such as `python3*`; see
+    /// [`find_executables_matching_in`](crate::find_executables_matching_in).
+    pub fn glob(&mut self, glob: bool) -> &mut Self {
+        self.probe.glob = glob;
+        self
+    }
+
+    /// Search exactly these directories, in order, instead of `PATH`.
+    pub fn search_path<I, P>(&mut self, dirs: I) -> &mut Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.search_path = Some(dirs.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Ignore pre-release versions such as `2.0.0-rc1`.
+    pub fn stable_only(&mut self, stable_only: bool) -> &mut Self {
+        self.selection.exclude_prereleases = stable_only;
+        self
+    }
+
+    /// Keep versions as reported, e.g. `3.11` rather than `3.11.0`, and rank a
+    /// component a tool left out as unknown rather than zero; see
+    /// [`compare_versions_partial`](crate::compare_versions_partial).
+    pub fn partial_versions(&mut self, partial_versions: bool) -> &mut Self {
+        self.probe.extract.normalize = if partial_versions {
+            NormalizePolicy::Exact
+        } else {
+            NormalizePolicy::default()
+        };
+        self.selection.partial_versions = partial_versions;
+        self
+    }
+
+    /// Make [`VersionFinder::latest`] select the oldest version instead.
+    pub fn oldest(&mut self, oldest: bool) -> &mut Self {
+        self.selection.oldest = oldest;
+        self
+    }
+
+    /// The executable with the latest version of `command`.
+    pub fn latest(&self, command: &str) -> Result<ExecutableInfo, LatestVersionError> {
+        select_latest(command, self.all(command)?, &self.selection)
+    }
+
+    /// Every executable for `command` whose version could be probed, in search order.
+    pub fn all(&self, command: &str) -> Result<Vec<ExecutableInfo>, LatestVersionError> {
+        self.stream(command, |_| {})
+    }
+
+    /// Like [`VersionFinder::all`], but also hands each executable to
+    /// `on_found` as soon as its version is known, e.g. to print it straight
+    /// away. Executables are probed in parallel, so `on_found` may be called
+    /// from several threads, in any order.
+    pub fn stream<F>(
+        &self,
+        command: &str,
+        on_found: F,
+    ) -> Result<Vec<ExecutableInfo>, LatestVersionError>
+    where
+        F: Fn(&ExecutableInfo) + Sync,
+    {
+        let executables = self.executables(command)?;
+        let (info_list, _) = probe_executables(executables, |executable| {
+            let result = get_version_with(executable, &self.probe);
+            if let Ok(info) = &result {
+                on_found(info);
+            }
+            result
+        });
+
+        Ok(info_list)
+    }
+
+    fn executables(&self, command: &str) -> Result<Vec<String>, LatestVersionError> {
+        let Some(dirs) = &self.search_path else {
+            return find_executables_with(command, &self.probe);
+        };
+
+        let dirs = without_dirs(
+            with_subdirs(dirs.clone(), self.probe.search_depth),
+            &self.probe.exclude_dirs,
+        );
+        if self.probe.glob {
+            find_executables_matching_in(command, &dirs)
+        } else {
+            find_executables_in(command, &dirs)
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tests::write_script;
+
+    #[test]
+    fn test_fully_configured_finder() {
+        let stable_dir = tempfile::tempdir().unwrap();
+        let rc_dir = tempfile::tempdir().unwrap();
+        let shim_dir = tempfile::tempdir().unwrap();
+        let partial_dir = tempfile::tempdir().unwrap();
+        // Only answers the custom flag.
+        let tool_body = |version: &str| {
+            format!(
+                "if [ \"$1\" = -V ]; then echo 'tool {}'; else echo 'tool 0.0.1'; fi",
+                version
+            )
+        };
+        let stable = write_script(stable_dir.path(), "tool", &tool_body("1.4.2"));
+        write_script(rc_dir.path(), "tool", &tool_body("1.5.0-rc1"));
+        write_script(shim_dir.path(), "tool", &tool_body("99.0.0"));
+        write_script(partial_dir.path(), "tool", &tool_body("2.0"));
+
+        let mut finder = VersionFinder::new();
+        finder
+            .search_path([
+                stable_dir.path(),
+                rc_dir.path(),
+                shim_dir.path(),
+                partial_dir.path(),
+            ])
+            .exclude_dir(shim_dir.path())
+            .flags(["-V"])
+            .timeout(Duration::from_secs(2))
+            .scheme(VersionScheme::Semver)
+            .stable_only(true);
+
+        let latest = finder.latest("tool").unwrap();
+        assert_eq!(latest.path, stable.to_str().unwrap());
+        assert_eq!(latest.version, "1.4.2");
+        assert_eq!(latest.probe_command, [stable.to_str().unwrap(), "-V"]);
+
+        let all: Vec<String> = finder
+            .all("tool")
+            .unwrap()
+            .into_iter()
+            .map(|info| info.version)
+            .collect();
+        assert_eq!(all, ["1.4.2", "1.5.0-rc1"]);
+
+        assert_eq!(finder.oldest(true).latest("tool").unwrap().version, "1.4.2");
+        assert_eq!(
+            finder
+                .stable_only(false)
+                .oldest(false)
+                .latest("tool")
+                .unwrap()
+                .version,
+            "1.5.0-rc1"
+        );
+
+        assert!(matches!(
+            VersionFinder::new()
+                .search_path([shim_dir.path()])
+                .exclude_dir(shim_dir.path())
+                .latest("tool"),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_reports_the_dir_each_executable_was_found_in() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let second_dir = tempfile::tempdir().unwrap();
+        let links = tempfile::tempdir().unwrap();
+        write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+        write_script(second_dir.path(), "tool", "echo 'tool 2.0.0'");
+        // Reported as the alias searched, not where it points.
+        let alias = links.path().join("bin");
+        std::os::unix::fs::symlink(second_dir.path(), &alias).unwrap();
+
+        let dirs: Vec<PathBuf> = VersionFinder::new()
+            .search_path([first_dir.path(), alias.as_path()])
+            .all("tool")
+            .unwrap()
+            .iter()
+            .map(|info| info.dir().unwrap().to_path_buf())
+            .collect();
+        assert_eq!(dirs, [first_dir.path(), alias.as_path()]);
+    }
+
+    #[test]
+    fn test_recursive_search() {
+        let root = tempfile::tempdir().unwrap();
+        let shallow = root.path().join("3.10/bin");
+        let deep = root.path().join("versions/3.12/bin");
+        std::fs::create_dir_all(&shallow).unwrap();
+        std::fs::create_dir_all(&deep).unwrap();
+        write_script(&shallow, "tool", "echo 'tool 3.10.4'");
+        let newest = write_script(&deep, "tool", "echo 'tool 3.12.1'");
+        // A loop back to the top must not be followed forever.
+        std::os::unix::fs::symlink(root.path(), deep.join("loop")).unwrap();
+
+        let versions = |depth| -> Vec<String> {
+            VersionFinder::new()
+                .search_path([root.path()])
+                .recursive(depth)
+                .all("tool")
+                .map(|all| all.into_iter().map(|info| info.version).collect())
+                .unwrap_or_default()
+        };
+        assert!(versions(0).is_empty());
+        assert!(versions(1).is_empty());
+        assert_eq!(versions(2), ["3.10.4"]);
+        assert_eq!(versions(3), ["3.10.4", "3.12.1"]);
+        assert_eq!(versions(usize::MAX), ["3.10.4", "3.12.1"]);
+
+        let latest = VersionFinder::new()
+            .search_path([root.path()])
+            .recursive(3)
+            .latest("tool")
+            .unwrap();
+        assert_eq!(latest.path, newest.to_str().unwrap());
+    }
+
+    #[test]
+    fn test_glob_finds_versioned_names() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        write_script(dir.path(), "python3.10", "echo 'Python 3.10.13'");
+        let newest = write_script(dir.path(), "python3.11", "echo 'Python 3.11.7'");
+        write_script(dir.path(), "pip3.12", "echo 'pip 24.0'");
+        // Matching, but not executable.
+        let readme = dir.path().join("python3.12.txt");
+        std::fs::write(&readme, "Python 3.12.1").unwrap();
+        std::fs::set_permissions(&readme, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut finder = VersionFinder::new();
+        finder.search_path([dir.path()]).glob(true);
+
+        let versions: Vec<String> = finder
+            .all("python*")
+            .unwrap()
+            .into_iter()
+            .map(|info| info.version)
+            .collect();
+        assert_eq!(versions, ["3.10.13", "3.11.7"]);
+        assert_eq!(
+            finder.latest("python*").unwrap().path,
+            newest.to_str().unwrap()
+        );
+        assert_eq!(finder.all("python3.1?").unwrap().len(), 2);
+
+        assert!(matches!(
+            finder.glob(false).latest("python*"),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+}
@@ -1,8 +1,63 @@
 use semver::Version;
-use std::process::{Command, Output};
+use std::cmp::Ordering;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use version_compare::Cmp;
-use which::which_in;
+use which::which;
+
+/// Compile a regex on first use and reuse it on every later call.
+macro_rules! static_regex {
+    ($pattern:expr) => {{
+        static REGEX: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        REGEX.get_or_init(|| regex::Regex::new($pattern).unwrap())
+    }};
+}
+
+/// Like [`static_regex!`], but for a pattern only known at run time, such as
+/// [`ExtractOptions::regex`]: compiled the first time any thread needs it and
+/// shared from then on. `None` if `pattern` isn't a valid regex.
+fn cached_regex(pattern: &str) -> Option<std::sync::Arc<regex::Regex>> {
+    type Cache = std::sync::Mutex<std::collections::HashMap<String, std::sync::Arc<regex::Regex>>>;
+    static CACHE: std::sync::OnceLock<Cache> = std::sync::OnceLock::new();
+
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(regex) = cache.get(pattern) {
+        return Some(std::sync::Arc::clone(regex));
+    }
+
+    let regex = std::sync::Arc::new(regex::Regex::new(pattern).ok()?);
+    cache.insert(pattern.to_string(), std::sync::Arc::clone(&regex));
+    Some(regex)
+}
+
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "cache")]
+mod cache;
+mod family;
+mod finder;
+mod provider;
+#[cfg(feature = "registry")]
+mod registry;
+
+#[cfg(feature = "async")]
+pub use asynchronous::find_latest_command_async;
+#[cfg(feature = "cache")]
+pub use cache::{get_version_cached, get_version_cached_with, VersionCache};
+pub use family::{find_latest_family, CommandFamilies, BUILTIN_FAMILIES};
+pub use finder::VersionFinder;
+pub use provider::{find_latest_command_via, LocalProvider, PrefixProvider, Provider};
+#[cfg(feature = "registry")]
+pub use registry::{latest_published, RegistrySource};
+
+/// The version of this crate, as in its `Cargo.toml`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg(feature = "pyo3")]
 include!("python_bindings.rs");
@@ -18,240 +73,4251 @@ pub enum LatestVersionError {
     #[error("Version extraction failed: {0}")]
     VersionExtractionError(String),
 
+    /// `path` ran, but nothing in what it printed looked like a version.
+    #[error("No version found in the output of {path}: {}", output_snippet(.output))]
+    NoVersionInOutput { path: String, output: String },
+
+    /// `path` printed nothing at all and failed, as tools that refuse to run
+    /// without a terminal tend to.
+    #[error("{path} printed nothing and failed ({status}); it may need to run in a terminal")]
+    SilentFailure { path: String, status: String },
+
     #[error("Failed to find executable paths")]
     PathFindingError(String),
 
     #[error("Failed to parse version: {0}")]
     VersionParsingError(#[from] semver::Error),
+
+    #[error("Version cache error: {0}")]
+    CacheError(String),
+
+    #[error("Multiple executables share the latest version: {}", .0.join(", "))]
+    AmbiguousLatest(Vec<String>),
+
+    #[error("Command timed out: {0}")]
+    Timeout(String),
+
+    #[error("Registry lookup failed: {0}")]
+    RegistryError(String),
+
+    /// `command` is installed, but no version of it satisfies `requirement`.
+    #[error("No version of {command} matches {requirement}")]
+    NoMatchingVersion {
+        command: String,
+        requirement: String,
+    },
 }
 
-#[derive(Debug, Clone)]
+impl LatestVersionError {
+    /// A short identifier for the kind of error that, unlike the message, never
+    /// changes between releases, e.g. `command-not-found`. The CLI prints it
+    /// after `Error` as `Error [command-not-found]: ...`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LatestVersionError::CommandNotFound(_) => "command-not-found",
+            LatestVersionError::CommandExecutionError(..) => "command-execution-failed",
+            LatestVersionError::VersionExtractionError(_) => "version-extraction-failed",
+            LatestVersionError::NoVersionInOutput { .. } => "no-version-in-output",
+            LatestVersionError::SilentFailure { .. } => "silent-failure",
+            LatestVersionError::PathFindingError(_) => "path-finding-failed",
+            LatestVersionError::VersionParsingError(_) => "version-parsing-failed",
+            LatestVersionError::CacheError(_) => "cache-error",
+            LatestVersionError::AmbiguousLatest(_) => "ambiguous-latest",
+            LatestVersionError::Timeout(_) => "timeout",
+            LatestVersionError::RegistryError(_) => "registry-lookup-failed",
+            LatestVersionError::NoMatchingVersion { .. } => "no-matching-version",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExecutableInfo {
     pub path: String,
     pub version: String,
+    /// The exact command line that produced `version`, e.g. `["/usr/bin/python3", "--version"]`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub probe_command: Vec<String>,
 }
 
-pub fn find_executables(command: &str) -> Result<Vec<String>, LatestVersionError> {
-    let path =
-        std::env::var("PATH").map_err(|e| LatestVersionError::PathFindingError(e.to_string()))?;
+impl ExecutableInfo {
+    pub fn new(path: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            version: version.into(),
+            probe_command: Vec::new(),
+        }
+    }
 
-    let mut executables = Vec::new();
+    /// The version as a strict [`semver::Version`].
+    pub fn parsed(&self) -> Result<Version, LatestVersionError> {
+        Ok(Version::parse(&self.version)?)
+    }
 
-    for dir in path.split(std::path::MAIN_SEPARATOR) {
-        if dir.is_empty() {
-            continue;
-        }
+    /// The major component, or `None` if the version isn't strict semver.
+    pub fn major(&self) -> Option<u64> {
+        self.parsed().ok().map(|version| version.major)
+    }
+
+    /// The minor component, or `None` if the version isn't strict semver.
+    pub fn minor(&self) -> Option<u64> {
+        self.parsed().ok().map(|version| version.minor)
+    }
+
+    /// The patch component, or `None` if the version isn't strict semver.
+    pub fn patch(&self) -> Option<u64> {
+        self.parsed().ok().map(|version| version.patch)
+    }
 
-        let dir_path = std::path::Path::new(dir);
+    /// The directory the executable was found in, as spelled in `PATH` or the
+    /// search path rather than with symlinks resolved. `None` for a bare name.
+    pub fn dir(&self) -> Option<&std::path::Path> {
+        std::path::Path::new(&self.path)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+    }
+
+    /// Whether the version is a semver pre-release such as `2.0.0-rc1`.
+    pub fn is_prerelease(&self) -> bool {
+        is_prerelease(&self.version)
+    }
+
+    /// The path as it is typed to run it: on Windows without a `PATHEXT`
+    /// extension such as `.exe`, which `cmd.exe` resolves in the same order as
+    /// the lookup did. Elsewhere the path is returned as is.
+    pub fn display_path(&self) -> &str {
+        #[cfg(windows)]
+        return strip_pathext(&self.path, &pathext());
+        #[cfg(not(windows))]
+        &self.path
+    }
+}
+
+/// Every executable named `command` on `PATH`, in order. With `PATH` unset
+/// there is nothing to search, so only an absolute `command` can be found.
+pub fn find_executables(command: &str) -> Result<Vec<String>, LatestVersionError> {
+    find_executables_excluding(command, &[])
+}
+
+/// Like [`find_executables`], but skips the `PATH` directories in `exclude_dirs`,
+/// e.g. one full of wrapper scripts. Directories are compared by canonical
+/// path, so excluding one also excludes any symlinked alias of it.
+pub fn find_executables_excluding(
+    command: &str,
+    exclude_dirs: &[PathBuf],
+) -> Result<Vec<String>, LatestVersionError> {
+    find_executables_recursive(command, exclude_dirs, 0)
+}
+
+/// The deepest [`find_executables_recursive`] searches below a `PATH` directory.
+pub const MAX_SEARCH_DEPTH: usize = 8;
 
-        if let Ok(found) = which_in(command, Some(dir_path), dir_path) {
-            if let Some(found_str) = found.to_str() {
-                executables.push(found_str.to_string());
+/// Like [`find_executables_excluding`], but also searches up to `depth` levels
+/// of subdirectories below each `PATH` directory, e.g. every install under
+/// `~/.pyenv/versions` when that is on `PATH`. `depth` is capped at
+/// [`MAX_SEARCH_DEPTH`], and a directory reached twice through symlinks is
+/// only searched once.
+pub fn find_executables_recursive(
+    command: &str,
+    exclude_dirs: &[PathBuf],
+    depth: usize,
+) -> Result<Vec<String>, LatestVersionError> {
+    match find_executables_in(command, &env_search_dirs(exclude_dirs, depth)) {
+        // Only an explicit path is looked up directly; `which` would otherwise
+        // resolve a bare name against the current directory.
+        Err(LatestVersionError::CommandNotFound(_))
+            if command.contains(std::path::is_separator) =>
+        {
+            match which(command) {
+                Ok(found) if !in_dirs(&found, exclude_dirs) && !is_empty_file(&found) => {
+                    Ok(found.to_str().map(str::to_string).into_iter().collect())
+                }
+                _ => Err(LatestVersionError::CommandNotFound(command.to_string())),
             }
         }
+        result => result,
     }
+}
 
-    if executables.is_empty() {
-        return Err(LatestVersionError::CommandNotFound(command.to_string()));
+/// Every executable the `find_*` functions taking `options` would probe for
+/// `command`: on `PATH` less `options.exclude_dirs`, down to
+/// `options.search_depth`, and matching `command` as a pattern if `options.glob`.
+pub fn find_executables_with(
+    command: &str,
+    options: &ProbeOptions,
+) -> Result<Vec<String>, LatestVersionError> {
+    if options.glob {
+        find_executables_matching_in(
+            command,
+            &env_search_dirs(&options.exclude_dirs, options.search_depth),
+        )
+    } else {
+        find_executables_recursive(command, &options.exclude_dirs, options.search_depth)
     }
+}
 
-    Ok(executables)
+/// The `PATH` directories to search, without `exclude_dirs` and with their
+/// subdirectories down to `depth`.
+fn env_search_dirs(exclude_dirs: &[PathBuf], depth: usize) -> Vec<PathBuf> {
+    // Minimal containers may legitimately run without a PATH; there is then
+    // simply nowhere to search.
+    let path = std::env::var_os("PATH").unwrap_or_else(|| {
+        log::debug!("PATH is not set");
+        Default::default()
+    });
+
+    without_dirs(with_subdirs(path_search_dirs(&path), depth), exclude_dirs)
 }
 
-pub fn extract_version(output: &str) -> Option<String> {
-    // Try to extract semantic version (x.y.z format)
-    let semver_pattern =
-        regex::Regex::new(r"(?P<major>\d+)\.(?P<minor>\d+)\.(?P<patch>\d+)").unwrap();
+/// `search_dirs`, each followed by its subdirectories down to `depth` levels
+/// (at most [`MAX_SEARCH_DEPTH`]), skipping any directory already visited.
+fn with_subdirs(search_dirs: Vec<PathBuf>, depth: usize) -> Vec<PathBuf> {
+    if depth == 0 {
+        return search_dirs;
+    }
 
-    if let Some(captures) = semver_pattern.captures(output) {
-        return Some(format!(
-            "{}.{}.{}",
-            &captures["major"], &captures["minor"], &captures["patch"]
-        ));
+    let mut dirs = Vec::new();
+    let mut visited = Vec::new();
+    for dir in search_dirs {
+        push_subdirs(dir, depth.min(MAX_SEARCH_DEPTH), &mut dirs, &mut visited);
     }
 
-    // Try to extract major.minor format
-    let minor_pattern = regex::Regex::new(r"(?P<major>\d+)\.(?P<minor>\d+)").unwrap();
+    dirs
+}
 
-    if let Some(captures) = minor_pattern.captures(output) {
-        return Some(format!("{}.{}.0", &captures["major"], &captures["minor"]));
+fn push_subdirs(dir: PathBuf, depth: usize, dirs: &mut Vec<PathBuf>, visited: &mut Vec<PathBuf>) {
+    let real_dir = canonical_dir(&dir);
+    if visited.contains(&real_dir) {
+        log::trace!("Already searched {}", dir.display());
+        return;
     }
+    visited.push(real_dir);
+    dirs.push(dir.clone());
 
-    // Try to extract just major version
-    let major_pattern = regex::Regex::new(r"(?P<major>\d+)").unwrap();
+    if depth == 0 {
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    // Sorted so the search order doesn't depend on the filesystem.
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+
+    for subdir in subdirs {
+        push_subdirs(subdir, depth - 1, dirs, visited);
+    }
+}
 
-    if let Some(captures) = major_pattern.captures(output) {
-        return Some(format!("{}.0.0", &captures["major"]));
+/// `search_dirs` without any that are, or resolve to, one of `exclude_dirs`.
+fn without_dirs(search_dirs: Vec<PathBuf>, exclude_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    if exclude_dirs.is_empty() {
+        return search_dirs;
     }
 
-    None
+    let excluded = canonical_dirs(exclude_dirs);
+    search_dirs
+        .into_iter()
+        .filter(|dir| {
+            let skip = excluded.contains(&canonical_dir(dir));
+            if skip {
+                log::debug!("Skipping excluded PATH entry {}", dir.display());
+            }
+            !skip
+        })
+        .collect()
 }
 
-pub fn get_version(executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
-    let mut command = Command::new(executable_path);
-    command.arg("--version");
+/// Whether `path` lives directly in one of `dirs`.
+fn in_dirs(path: &std::path::Path, dirs: &[PathBuf]) -> bool {
+    path.parent()
+        .is_some_and(|parent| canonical_dirs(dirs).contains(&canonical_dir(parent)))
+}
 
-    let output: Output = command
-        .output()
-        .map_err(|e| LatestVersionError::CommandExecutionError(executable_path.to_string(), e))?;
+fn canonical_dirs(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    dirs.iter().map(|dir| canonical_dir(dir)).collect()
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+/// `dir` with symlinks resolved, or as given if it doesn't exist.
+fn canonical_dir(dir: &std::path::Path) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
 
-    let combined_output = format!("{}{}", stdout, stderr);
+/// At most the first 200 characters of `output`, quoted onto one line.
+fn output_snippet(output: &str) -> String {
+    const MAX_CHARS: usize = 200;
 
-    if let Some(version_str) = extract_version(&combined_output) {
-        Ok(ExecutableInfo {
-            path: executable_path.to_string(),
-            version: version_str,
+    let output = output.trim();
+    match output.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{:?}...", &output[..end]),
+        None => format!("{:?}", output),
+    }
+}
+
+/// The first executable named `command` on `PATH`, like `which`, without running
+/// it. Nothing is probed, so this is safe to use on untrusted binaries.
+pub fn find_first(command: &str) -> Result<String, LatestVersionError> {
+    find_executables(command)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| LatestVersionError::CommandNotFound(command.to_string()))
+}
+
+/// The executable a shell would run for `command`, the first on `PATH`, with
+/// its version. Compare it with [`find_latest_command`] to tell whether a newer
+/// install is being shadowed.
+pub fn resolve_active(command: &str) -> Result<ExecutableInfo, LatestVersionError> {
+    resolve_active_with(command, &ProbeOptions::default())
+}
+
+/// Like [`resolve_active`], but probes according to `probe`. Its `exclude_dirs`
+/// and `search_depth` are ignored, since the shell doesn't honour them.
+pub fn resolve_active_with(
+    command: &str,
+    probe: &ProbeOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    get_version_with(&find_first(command)?, probe)
+}
+
+/// Search a `PATH`-style list of directories separated by `sep`, such as a
+/// `;`-separated Windows `PATH` captured on another machine.
+///
+/// Empty entries and relative entries such as `.` are skipped rather than
+/// resolved against the current directory, so running from an untrusted
+/// directory can never pick up (and execute) whatever happens to live there.
+pub fn find_executables_in_with_sep(
+    command: &str,
+    path: &str,
+    sep: char,
+) -> Result<Vec<String>, LatestVersionError> {
+    find_executables_in(command, &search_dirs(path.split(sep).map(PathBuf::from)))
+}
+
+/// The entries of a `PATH`-style list, split on this platform's list separator
+/// (`:` on Unix, `;` on Windows), that are safe to search.
+fn path_search_dirs(path: &std::ffi::OsStr) -> Vec<PathBuf> {
+    search_dirs(std::env::split_paths(path))
+}
+
+/// The entries of a `PATH` that are safe to search.
+fn search_dirs(entries: impl Iterator<Item = PathBuf>) -> Vec<PathBuf> {
+    entries
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .filter(|dir| {
+            let absolute = dir.is_absolute();
+            if !absolute {
+                log::debug!("Skipping relative PATH entry {}", dir.display());
+            }
+            absolute
         })
-    } else {
-        // Try other version flags if --version failed
-        for flag in ["-v", "-V", "version"] {
-            let mut command = Command::new(executable_path);
-            command.arg(flag);
-
-            match command.output() {
-                Ok(output) => {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let combined_output = format!("{}{}", stdout, stderr);
-
-                    if let Some(version_str) = extract_version(&combined_output) {
-                        return Ok(ExecutableInfo {
-                            path: executable_path.to_string(),
-                            version: version_str,
-                        });
-                    }
-                }
-                Err(_) => continue,
+        .collect()
+}
+
+/// Entries of the `PATH` environment variable that don't exist or aren't directories.
+pub fn missing_path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| missing_dirs(std::env::split_paths(&path)))
+        .unwrap_or_default()
+}
+
+/// Like [`missing_path_dirs`], for a `PATH`-style list separated by `sep`.
+pub fn missing_dirs_in_with_sep(path: &str, sep: char) -> Vec<PathBuf> {
+    missing_dirs(path.split(sep).map(PathBuf::from))
+}
+
+fn missing_dirs(entries: impl Iterator<Item = PathBuf>) -> Vec<PathBuf> {
+    entries
+        .filter(|dir| !dir.as_os_str().is_empty() && !dir.is_dir())
+        .collect()
+}
+
+/// What a scan found in a single search directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirScan {
+    pub dir: PathBuf,
+    pub exists: bool,
+    /// Files named like the command, whether or not they are executable.
+    pub matches: Vec<FileMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileMatch {
+    pub path: PathBuf,
+    pub executable: bool,
+}
+
+/// A per-directory account of how a command was (or wasn't) found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanReport {
+    pub command: String,
+    pub dirs: Vec<DirScan>,
+}
+
+impl std::fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Scan for '{}':", self.command)?;
+
+        for scan in &self.dirs {
+            if !scan.exists {
+                writeln!(f, "  {}: does not exist", scan.dir.display())?;
+                continue;
+            }
+
+            writeln!(
+                f,
+                "  {}: {} match(es)",
+                scan.dir.display(),
+                scan.matches.len()
+            )?;
+            for file in &scan.matches {
+                let kind = if file.executable {
+                    "executable"
+                } else {
+                    "not executable"
+                };
+                writeln!(f, "    {} ({})", file.path.display(), kind)?;
             }
         }
 
-        Err(LatestVersionError::VersionExtractionError(
-            "No version information found".to_string(),
-        ))
+        Ok(())
     }
 }
 
-pub fn find_latest_version(
-    info_list: Vec<ExecutableInfo>,
-) -> Result<ExecutableInfo, LatestVersionError> {
-    let mut latest_info = None;
+/// Scan the directories on `PATH` for `command`, reporting on each one.
+pub fn scan_path(command: &str) -> ScanReport {
+    let search_dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
 
-    for info in info_list {
-        match Version::parse(&info.version) {
-            Ok(parsed_version) => match &latest_info {
-                None => latest_info = Some(info),
-                Some(latest) => match Version::parse(&latest.version) {
-                    Ok(latest_version) => {
-                        if parsed_version > latest_version {
-                            latest_info = Some(info);
-                        }
-                    }
-                    Err(_) => {
-                        latest_info = Some(info);
-                    }
-                },
-            },
-            Err(_) => {
-                // Fallback to flexible version comparison
-                match &latest_info {
-                    None => latest_info = Some(info),
-                    Some(latest) => {
-                        match version_compare::compare(&info.version, &latest.version) {
-                            Ok(Cmp::Gt) => latest_info = Some(info),
-                            _ => continue,
-                        }
-                    }
-                }
-            }
-        }
+    scan_dirs(command, &search_dirs)
+}
+
+/// Scan `search_dirs` for `command`, reporting on each one.
+pub fn scan_dirs(command: &str, search_dirs: &[PathBuf]) -> ScanReport {
+    let dirs = search_dirs
+        .iter()
+        .map(|dir| DirScan {
+            dir: dir.clone(),
+            exists: dir.is_dir(),
+            matches: candidate_files(dir, command)
+                .into_iter()
+                .map(|path| FileMatch {
+                    executable: is_executable_file(&path),
+                    path,
+                })
+                .collect(),
+        })
+        .collect();
+
+    ScanReport {
+        command: command.to_string(),
+        dirs,
     }
+}
 
-    latest_info.ok_or(LatestVersionError::VersionExtractionError(
-        "No valid versions found".to_string(),
-    ))
+/// Files in `dir` that could be `command`, executable or not.
+fn candidate_files(dir: &std::path::Path, command: &str) -> Vec<PathBuf> {
+    candidate_names(command)
+        .into_iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.symlink_metadata().is_ok())
+        .collect()
 }
 
-pub fn find_latest_command(command: &str) -> Result<ExecutableInfo, LatestVersionError> {
-    let executables = find_executables(command)?;
+#[cfg(not(windows))]
+fn candidate_names(command: &str) -> Vec<String> {
+    vec![command.to_string()]
+}
 
-    let mut info_list = Vec::new();
+/// `command` as given, then with each `PATHEXT` extension appended in order.
+#[cfg(windows)]
+fn candidate_names(command: &str) -> Vec<String> {
+    candidate_names_with(command, &pathext())
+}
 
-    for executable in executables {
-        match get_version(&executable) {
-            Ok(info) => info_list.push(info),
-            Err(_) => continue,
+#[cfg(windows)]
+fn candidate_names_with(command: &str, pathext: &str) -> Vec<String> {
+    std::iter::once(command.to_string())
+        .chain(
+            pathext
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .map(|ext| format!("{}{}", command, ext)),
+        )
+        .collect()
+}
+
+/// The executable that running `command` from `dir` would start.
+///
+/// On Windows that is the first candidate name that exists, so `tool.exe` wins
+/// over `tool.bat` when `.EXE` comes first in `PATHEXT`, as it does for `cmd.exe`.
+fn resolve_in(dir: &std::path::Path, command: &str) -> Option<PathBuf> {
+    candidate_names(command)
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| is_executable_file(path))
+}
+
+/// The `PATHEXT` list of executable extensions, with the usual default if unset.
+#[cfg(windows)]
+fn pathext() -> String {
+    std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into())
+}
+
+/// Whether `path` ends in one of the `;`-separated extensions in `pathext`,
+/// ignoring case.
+#[cfg(windows)]
+fn has_pathext_extension(path: &std::path::Path, pathext: &str) -> bool {
+    let Some(extension) = path.extension().and_then(|extension| extension.to_str()) else {
+        return false;
+    };
+
+    pathext
+        .split(';')
+        .filter_map(|ext| ext.strip_prefix('.'))
+        .any(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// `path` without its extension if that is one of the extensions in `pathext`.
+#[cfg(windows)]
+fn strip_pathext<'a>(path: &'a str, pathext: &str) -> &'a str {
+    if !has_pathext_extension(std::path::Path::new(path), pathext) {
+        return path;
+    }
+
+    path.rfind('.').map_or(path, |dot| &path[..dot])
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file())
+        && has_pathext_extension(path, &pathext())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file())
+}
+
+/// Every executable named `command` in `search_dirs`, searched in order.
+///
+/// Unlike [`find_executables`], this never reads the environment, and the
+/// directories are searched as given.
+pub fn find_executables_in(
+    command: &str,
+    search_dirs: &[PathBuf],
+) -> Result<Vec<String>, LatestVersionError> {
+    collect_executables(command, search_dirs, |dir_path| named_in(dir_path, command))
+}
+
+/// Like [`find_executables`], but yields each executable as soon as the `PATH`
+/// directory holding it has been checked, so a caller that stops early never
+/// scans the rest. An empty iterator means `command` wasn't found.
+pub fn iter_executables(command: &str) -> impl Iterator<Item = String> + '_ {
+    iter_executables_in(command, env_search_dirs(&[], 0))
+}
+
+/// Like [`find_executables_in`], but lazy; see [`iter_executables`]. Each
+/// directory is only taken from `search_dirs` once the previous one is done.
+pub fn iter_executables_in<'a, I>(
+    command: &'a str,
+    search_dirs: I,
+) -> impl Iterator<Item = String> + 'a
+where
+    I: IntoIterator<Item = PathBuf>,
+    I::IntoIter: 'a,
+{
+    scan_executables(command, search_dirs, move |dir_path| {
+        named_in(dir_path, command)
+    })
+}
+
+/// The executable named `command` in `dir_path`, if there is one.
+fn named_in(dir_path: &std::path::Path, command: &str) -> Vec<PathBuf> {
+    let candidate = dir_path.join(command);
+
+    if is_dangling_symlink(&candidate) {
+        log::debug!("Skipping dangling symlink {}", candidate.display());
+        return Vec::new();
+    }
+
+    resolve_in(dir_path, command).into_iter().collect()
+}
+
+/// Like [`find_executables_in`], but finds every executable whose name matches
+/// `pattern`, in which `*` stands for any run of characters and `?` for any one,
+/// e.g. `python3*` for `python3`, `python3.10` and `python3.11`. Matches within
+/// a directory are taken in name order.
+pub fn find_executables_matching_in(
+    pattern: &str,
+    search_dirs: &[PathBuf],
+) -> Result<Vec<String>, LatestVersionError> {
+    collect_executables(pattern, search_dirs, |dir_path| {
+        let Ok(entries) = std::fs::read_dir(dir_path) else {
+            return Vec::new();
+        };
+
+        let mut matches: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| glob_matches(pattern, name))
+            })
+            .map(|entry| entry.path())
+            .filter(|path| is_executable_file(path))
+            .collect();
+        matches.sort();
+        matches
+    })
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters
+/// (including none) and `?` any single character.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was, and how much of `name` it has swallowed so far.
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry.
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
         }
     }
 
-    if info_list.is_empty() {
-        return Err(LatestVersionError::VersionExtractionError(format!(
-            "No version information found for command '{}'",
-            command
-        )));
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// The executables that `lookup` finds in each of `search_dirs`, in order,
+/// without empty files or second paths to the same file.
+fn collect_executables<F>(
+    command: &str,
+    search_dirs: &[PathBuf],
+    lookup: F,
+) -> Result<Vec<String>, LatestVersionError>
+where
+    F: Fn(&std::path::Path) -> Vec<PathBuf>,
+{
+    let executables: Vec<String> =
+        scan_executables(command, search_dirs.iter().cloned(), lookup).collect();
+
+    if executables.is_empty() {
+        return Err(LatestVersionError::CommandNotFound(command.to_string()));
     }
 
-    find_latest_version(info_list)
+    Ok(executables)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The executables `lookup` finds in each of `search_dirs`, in order, skipping
+/// empty files and any already yielded under another path.
+fn scan_executables<'a, I, F>(
+    command: &'a str,
+    search_dirs: I,
+    lookup: F,
+) -> impl Iterator<Item = String> + 'a
+where
+    I: IntoIterator<Item = PathBuf>,
+    I::IntoIter: 'a,
+    F: Fn(&std::path::Path) -> Vec<PathBuf> + 'a,
+{
+    let mut real_paths = Vec::new();
 
-    #[test]
-    fn test_semantic_version_parsing() {
-        let output = "Python 3.11.4";
-        let version = extract_version(output);
-        assert_eq!(version, Some("3.11.4".to_string()));
+    search_dirs
+        .into_iter()
+        .flat_map(move |dir_path| {
+            let found_in_dir = lookup(&dir_path);
+            if found_in_dir.is_empty() {
+                log::trace!("No {} in {}", command, dir_path.display());
+            }
+            found_in_dir
+        })
+        .filter_map(move |found| {
+            if is_empty_file(&found) {
+                log::debug!("Skipping empty file {}", found.display());
+                return None;
+            }
+
+            // Symlinks and repeated PATH entries lead to the same file; probe it
+            // once, under the first path it was found at.
+            let real_path = std::fs::canonicalize(&found).unwrap_or_else(|_| found.clone());
+            if real_paths.contains(&real_path) {
+                log::debug!("Skipping duplicate {}", found.display());
+                return None;
+            }
+
+            let found_str = found.to_str()?.to_string();
+            real_paths.push(real_path);
+            Some(found_str)
+        })
+}
+
+/// How a matched version number is rendered into the returned string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalizePolicy {
+    /// Pad missing components with zeros, so `3.11` becomes `3.11.0`.
+    #[default]
+    PadToPatch,
+    /// Return exactly the components that were matched, so `3.11` stays `3.11`.
+    Exact,
+    /// Keep only `major.minor`, padding or truncating as needed.
+    MajorMinor,
+}
+
+/// Which kind of version [`extract_version_with`] looks for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionScheme {
+    /// Only strict `major.minor.patch` versions, with optional pre-release and
+    /// build metadata. Output without one yields no version rather than a
+    /// coerced one.
+    Semver,
+    /// The first run of dot-separated numbers, however many components it
+    /// has, e.g. `2.3.4.5`.
+    Lenient,
+    /// Calendar versions written `YYYY.MM.DD` or `YYYYMMDD`, returned as
+    /// `YYYY.M.D` so snapshot builds order chronologically.
+    CalVer,
+    /// Semver first, then progressively looser forms down to a bare major number.
+    #[default]
+    Auto,
+}
+
+impl std::fmt::Display for VersionScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            VersionScheme::Semver => "semver",
+            VersionScheme::Lenient => "lenient",
+            VersionScheme::CalVer => "calver",
+            VersionScheme::Auto => "auto",
+        })
     }
+}
 
-    #[test]
-    fn test_major_minor_version_parsing() {
-        let output = "Node.js v18.16.0";
-        let version = extract_version(output);
-        assert_eq!(version, Some("18.16.0".to_string()));
+impl std::str::FromStr for VersionScheme {
+    type Err = ParseSchemeError;
+
+    /// Parse a scheme's name as written by `Display`, ignoring case, e.g.
+    /// `"semver"` or `"CalVer"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "semver" => Ok(VersionScheme::Semver),
+            "lenient" => Ok(VersionScheme::Lenient),
+            "calver" => Ok(VersionScheme::CalVer),
+            "auto" => Ok(VersionScheme::Auto),
+            _ => Err(ParseSchemeError(name.to_string())),
+        }
     }
+}
 
-    #[test]
-    fn test_major_version_parsing() {
-        let output = "Git version 2";
-        let version = extract_version(output);
-        assert_eq!(version, Some("2.0.0".to_string()));
+/// The error from parsing an unknown [`VersionScheme`] name.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown version scheme {0:?}; expected one of semver, lenient, calver or auto")]
+pub struct ParseSchemeError(String);
+
+/// Options controlling how [`extract_version_with`] scans probe output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// Skip numbers that are part of a URL (`scheme://...`), unless the output
+    /// contains no other candidate.
+    pub ignore_urls: bool,
+    /// Format of the returned version string.
+    pub normalize: NormalizePolicy,
+    /// Kind of version to look for.
+    pub scheme: VersionScheme,
+    /// Only look at the number following this word (matched case-insensitively),
+    /// e.g. `release` for `Foo 2.0 (build 12345) release 3.4.1`.
+    pub anchor: Option<String>,
+    /// Take the Nth run of numbers, counting from 1 (after the anchor, if there
+    /// is one), e.g. `2` for the build number in `Foo 2.0 (build 12345)`.
+    pub nth: Option<usize>,
+    /// A regex to use instead of the built-in patterns, with the version in a
+    /// group named `version`; see [`extract_version_with_regex`].
+    pub regex: Option<String>,
+}
+
+/// Every distinct `major.minor.patch` version in `output`, in the order they appear.
+///
+/// Useful for tools like `docker version` that report both a client and a server version.
+pub fn extract_all_versions(output: &str) -> Vec<String> {
+    let semver_pattern = static_regex!(r"(?P<major>\d+)\.(?P<minor>\d+)\.(?P<patch>\d+)");
+
+    let mut versions: Vec<String> = Vec::new();
+
+    for captures in semver_pattern.captures_iter(&clean_output(output)) {
+        let version = format!(
+            "{}.{}.{}",
+            &captures["major"], &captures["minor"], &captures["patch"]
+        );
+
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
     }
 
-    #[test]
-    fn test_version_comparison() {
-        let info1 = ExecutableInfo {
-            path: "/usr/bin/python3".to_string(),
-            version: "3.10.0".to_string(),
-        };
+    versions
+}
 
-        let info2 = ExecutableInfo {
-            path: "/usr/local/bin/python3".to_string(),
-            version: "3.11.0".to_string(),
-        };
+/// Whether `path` is a zero-byte file, such as a placeholder left by an
+/// interrupted install, which can't be run.
+fn is_empty_file(path: &std::path::Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.len() == 0)
+}
 
-        let latest = find_latest_version(vec![info1, info2]).unwrap();
-        assert_eq!(latest.path, "/usr/local/bin/python3");
-        assert_eq!(latest.version, "3.11.0");
+fn is_dangling_symlink(path: &std::path::Path) -> bool {
+    path.symlink_metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+        && std::fs::metadata(path).is_err()
+}
+
+pub fn extract_version_with(output: &str, options: &ExtractOptions) -> Option<String> {
+    if let Some(pattern) = &options.regex {
+        return extract_version_with_regex(&strip_ansi(output), &*cached_regex(pattern)?);
     }
 
-    #[test]
-    fn test_fallback_version_comparison() {
-        let info1 = ExecutableInfo {
-            path: "/usr/bin/java".to_string(),
-            version: "1.8.0_302".to_string(),
-        };
+    let output = &*clean_output(output);
 
-        let info2 = ExecutableInfo {
-            path: "/usr/local/bin/java".to_string(),
-            version: "11.0.16".to_string(),
-        };
+    if options.ignore_urls {
+        let url_pattern = static_regex!(r"[A-Za-z][A-Za-z0-9+.-]*://\S+");
 
-        let latest = find_latest_version(vec![info1, info2]).unwrap();
-        assert_eq!(latest.path, "/usr/local/bin/java");
-        assert_eq!(latest.version, "11.0.16");
+        // Blank out URLs rather than removing them so unrelated numbers on
+        // either side can't run together.
+        let without_urls = url_pattern.replace_all(output, |captures: &regex::Captures| {
+            " ".repeat(captures[0].len())
+        });
+
+        if let Some(parts) =
+            focus(&without_urls, options).and_then(|text| match_scheme(text, options.scheme))
+        {
+            return Some(parts.render(options.normalize));
+        }
+    }
+
+    focus(output, options)
+        .and_then(|text| match_scheme(text, options.scheme))
+        .map(|parts| parts.render(options.normalize))
+}
+
+/// The text captured by the group named `version` in the first match of
+/// `pattern`, or the whole match if there is no such group, returned as is,
+/// e.g. `2024w05` from `Tool v2024w05` with `v(?P<version>\d{4}w\d{2})`.
+pub fn extract_version_with_regex(output: &str, pattern: &regex::Regex) -> Option<String> {
+    let captures = pattern.captures(output)?;
+    let version = captures.name("version").or_else(|| captures.get(0))?;
+
+    Some(version.as_str().to_string())
+}
+
+/// The part of `output` that [`ExtractOptions::anchor`] and [`ExtractOptions::nth`]
+/// point at: the single word holding the chosen run of numbers, or all of
+/// `output` if neither is set.
+fn focus<'a>(output: &'a str, options: &ExtractOptions) -> Option<&'a str> {
+    if options.anchor.is_none() && options.nth.is_none() {
+        return Some(output);
+    }
+
+    let text = match &options.anchor {
+        Some(anchor) => {
+            let anchor_pattern = cached_regex(&format!(r"(?i)\b{}\b", regex::escape(anchor)))?;
+            &output[anchor_pattern.find(output)?.end()..]
+        }
+        None => output,
+    };
+
+    let nth = options.nth.unwrap_or(1).max(1);
+    let number = static_regex!(r"\d+(?:\.\d+)*")
+        .find_iter(text)
+        .nth(nth - 1)?;
+
+    let rest = &text[number.start()..];
+    rest.split_whitespace().next()
+}
+
+fn match_scheme(output: &str, scheme: VersionScheme) -> Option<VersionParts<'_>> {
+    match scheme {
+        // Leading zeros and Java-style update numbers aren't valid semver.
+        VersionScheme::Semver => match_semver(output)
+            .filter(|parts| Version::parse(&parts.render(NormalizePolicy::PadToPatch)).is_ok()),
+        VersionScheme::Lenient => match_lenient(output),
+        VersionScheme::CalVer => match_calver(output),
+        VersionScheme::Auto => match_version(output),
+    }
+}
+
+fn match_lenient(output: &str) -> Option<VersionParts<'_>> {
+    let lenient_pattern = static_regex!(
+        r"(?P<major>\d+)(?:\.(?P<minor>\d+))?(?:\.(?P<patch>\d+))?(?P<suffix>(?:\.\d+)*)"
+    );
+
+    let captures = lenient_pattern.captures(output)?;
+
+    Some(VersionParts {
+        major: captures.name("major")?.as_str(),
+        minor: captures.name("minor").map(|minor| minor.as_str()),
+        patch: captures.name("patch").map(|patch| patch.as_str()),
+        suffix: captures.name("suffix").map_or("", |suffix| suffix.as_str()),
+    })
+}
+
+fn match_calver(output: &str) -> Option<VersionParts<'_>> {
+    let calver_pattern = static_regex!(
+        r"\b(?P<year>\d{4})(?:\.(?P<dotted_month>\d{2})\.(?P<dotted_day>\d{2})|(?P<month>\d{2})(?P<day>\d{2}))\b"
+    );
+
+    calver_pattern.captures_iter(output).find_map(|captures| {
+        let month = captures
+            .name("dotted_month")
+            .or_else(|| captures.name("month"))?
+            .as_str();
+        let day = captures
+            .name("dotted_day")
+            .or_else(|| captures.name("day"))?
+            .as_str();
+
+        // Reject numbers that merely look like dates, e.g. a build number of 20231399.
+        let valid_month = (1..=12).contains(&month.parse::<u32>().ok()?);
+        let valid_day = (1..=31).contains(&day.parse::<u32>().ok()?);
+        if !(valid_month && valid_day) {
+            return None;
+        }
+
+        Some(VersionParts {
+            major: captures.name("year")?.as_str(),
+            minor: Some(month.trim_start_matches('0')),
+            patch: Some(day.trim_start_matches('0')),
+            suffix: "",
+        })
+    })
+}
+
+/// `output` ready for the version patterns: without ANSI escapes, glued-on
+/// prefixes or numbers too long to be a version component.
+fn clean_output(output: &str) -> String {
+    let output = strip_ansi(output);
+    // Structured output says exactly which value is the version.
+    let output = json_version_field(&output).map_or(output, std::borrow::Cow::Owned);
+
+    blank_long_numbers(&strip_version_prefixes(&output)).into_owned()
+}
+
+/// The value of a top-level `version`, `Version` or `tag` key when `output`
+/// is a JSON object, as printed by tools with a `--version --format json`.
+#[cfg(feature = "serde")]
+fn json_version_field(output: &str) -> Option<String> {
+    let output = output.trim();
+    if !output.starts_with('{') {
+        return None;
+    }
+
+    let json: serde_json::Value = serde_json::from_str(output).ok()?;
+    ["version", "Version", "tag"]
+        .iter()
+        .find_map(|key| match json.get(key)? {
+            serde_json::Value::String(version) => Some(version.clone()),
+            serde_json::Value::Number(version) => Some(version.to_string()),
+            _ => None,
+        })
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_version_field(_output: &str) -> Option<String> {
+    None
+}
+
+/// Blank out runs of 20 or more digits, such as a giant build number, which
+/// overflow a semver component and can't be part of a real version.
+fn blank_long_numbers(output: &str) -> std::borrow::Cow<'_, str> {
+    let long_number = static_regex!(r"\d{20,}");
+
+    // Blanked rather than removed so the numbers on either side stay apart.
+    long_number.replace_all(output, |captures: &regex::Captures| {
+        " ".repeat(captures[0].len())
+    })
+}
+
+/// Remove ANSI escape sequences, such as the colors of a fancy banner, whose
+/// numbers would otherwise read as a version: `\x1b[38;5;208m` is not `38.5.208`.
+fn strip_ansi(output: &str) -> std::borrow::Cow<'_, str> {
+    // CSI sequences like colors and cursor movement, then OSC sequences like
+    // hyperlinks and window titles, which end in BEL or ST.
+    let ansi_pattern = static_regex!(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)");
+
+    ansi_pattern.replace_all(output, "")
+}
+
+/// Drop a `v`, `V`, `go` or `jdk` prefix glued to the front of a number, as in
+/// `v18.16.0`, `go1.21.4` or `jdk17.0.2`, so the number stands alone as a word.
+fn strip_version_prefixes(output: &str) -> std::borrow::Cow<'_, str> {
+    let prefix_pattern = static_regex!(r"\b(?:[vV]|go|jdk)(\d)");
+
+    prefix_pattern.replace_all(output, "$1")
+}
+
+/// The first version in `output`, padded to `major.minor.patch`. A `v`, `V`,
+/// `go` or `jdk` prefix glued to the number is accepted and dropped.
+pub fn extract_version(output: &str) -> Option<String> {
+    match_version(&clean_output(output)).map(|parts| parts.render(NormalizePolicy::PadToPatch))
+}
+
+/// The numeric components of a version found in probe output.
+struct VersionParts<'a> {
+    major: &'a str,
+    minor: Option<&'a str>,
+    patch: Option<&'a str>,
+    /// Pre-release and build metadata after the patch number, e.g. `-rc1+build.5`,
+    /// or a Java-style update number such as `_302`.
+    suffix: &'a str,
+}
+
+impl VersionParts<'_> {
+    fn render(&self, policy: NormalizePolicy) -> String {
+        match policy {
+            NormalizePolicy::PadToPatch => format!(
+                "{}.{}.{}{}",
+                self.major,
+                self.minor.unwrap_or("0"),
+                self.patch.unwrap_or("0"),
+                self.suffix
+            ),
+            NormalizePolicy::Exact => match (self.minor, self.patch) {
+                (Some(minor), Some(patch)) => {
+                    format!("{}.{}.{}{}", self.major, minor, patch, self.suffix)
+                }
+                (Some(minor), None) => format!("{}.{}", self.major, minor),
+                _ => self.major.to_string(),
+            },
+            NormalizePolicy::MajorMinor => {
+                format!("{}.{}", self.major, self.minor.unwrap_or("0"))
+            }
+        }
+    }
+}
+
+fn match_version(output: &str) -> Option<VersionParts<'_>> {
+    if let Some(parts) = match_semver(output) {
+        return Some(parts);
+    }
+
+    // Try to extract major.minor-patch format (e.g. 1.2-3)
+    let dashed_pattern = static_regex!(r"(?P<major>\d+)\.(?P<minor>\d+)-(?P<patch>\d+)\b");
+
+    if let Some(captures) = dashed_pattern.captures(output) {
+        return Some(VersionParts {
+            major: captures.name("major")?.as_str(),
+            minor: Some(captures.name("minor")?.as_str()),
+            patch: Some(captures.name("patch")?.as_str()),
+            suffix: "",
+        });
+    }
+
+    // Try to extract major.minor format
+    let minor_pattern = static_regex!(r"(?P<major>\d+)\.(?P<minor>\d+)");
+
+    if let Some(captures) = minor_pattern.captures(output) {
+        return Some(VersionParts {
+            major: captures.name("major")?.as_str(),
+            minor: Some(captures.name("minor")?.as_str()),
+            patch: None,
+            suffix: "",
+        });
+    }
+
+    // Try to extract just major version
+    let major_pattern = static_regex!(r"(?P<major>\d+)");
+
+    major_pattern.captures(output).and_then(|captures| {
+        Some(VersionParts {
+            major: captures.name("major")?.as_str(),
+            minor: None,
+            patch: None,
+            suffix: "",
+        })
+    })
+}
+
+fn match_semver(output: &str) -> Option<VersionParts<'_>> {
+    // Try to extract semantic version (x.y.z format), keeping any pre-release
+    // and build metadata so that 1.2.0-rc1 sorts below 1.2.0, or a Java-style
+    // update number (1.8.0_302) that the flexible comparison orders as a fourth
+    // component
+    let semver_pattern = static_regex!(
+        r"(?P<major>\d+)\.(?P<minor>\d+)\.(?P<patch>\d+)(?P<suffix>_\d+|(?:-[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*)?(?:\+[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*)?)"
+    );
+
+    let captures = semver_pattern.captures(output)?;
+
+    Some(VersionParts {
+        major: captures.name("major")?.as_str(),
+        minor: Some(captures.name("minor")?.as_str()),
+        patch: Some(captures.name("patch")?.as_str()),
+        suffix: captures.name("suffix").map_or("", |suffix| suffix.as_str()),
+    })
+}
+
+/// Tools that don't answer the usual `--version`, and the flags they do answer.
+const BUILTIN_VERSION_FLAGS: &[(&str, &[&str])] = &[
+    ("java", &["-version"]),
+    ("javac", &["-version"]),
+    ("go", &["version"]),
+    ("ssh", &["-V"]),
+    ("tmux", &["-V"]),
+    ("openssl", &["version"]),
+    ("ffmpeg", &["-version"]),
+    ("lua", &["-v"]),
+];
+
+const DEFAULT_VERSION_FLAGS: &[&str] = &["--version", "-v", "-V", "version"];
+
+/// Commands with special version-flag handling, and the flags tried first for them.
+pub fn builtin_version_flags() -> &'static [(&'static str, &'static [&'static str])] {
+    BUILTIN_VERSION_FLAGS
+}
+
+/// The flags to try for `executable_path`: any built-in ones for the tool,
+/// followed by the defaults.
+fn version_flags_for(executable_path: &str) -> Vec<&'static str> {
+    let file_name = std::path::Path::new(executable_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let name = file_name
+        .len()
+        .checked_sub(4)
+        .filter(|&stem_len| {
+            file_name
+                .get(stem_len..)
+                .is_some_and(|extension| extension.eq_ignore_ascii_case(".exe"))
+        })
+        .map_or(file_name, |stem_len| &file_name[..stem_len]);
+
+    let mut flags: Vec<&'static str> = BUILTIN_VERSION_FLAGS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(_, flags)| flags.to_vec())
+        .unwrap_or_default();
+
+    for flag in DEFAULT_VERSION_FLAGS {
+        if !flags.contains(flag) {
+            flags.push(flag);
+        }
+    }
+
+    flags
+}
+
+/// How long a probe may run before it is killed, unless overridden in [`ProbeOptions`].
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Overrides [`DEFAULT_PROBE_TIMEOUT`] in [`ProbeOptions::default`], in milliseconds.
+pub const TIMEOUT_ENV_VAR: &str = "LATEST_VERSION_TIMEOUT_MS";
+
+/// Comma-separated flags that [`ProbeOptions::default`] tries instead of the
+/// built-in sequence, e.g. `-V,--version`.
+pub const FLAGS_ENV_VAR: &str = "LATEST_VERSION_FLAGS";
+
+/// Options controlling how [`get_version_with`] runs an executable, and which
+/// executables the `find_*` functions taking them probe.
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    /// Written to the child's stdin before its output is read, for filter-style
+    /// tools that only report a version when fed some input.
+    pub stdin_input: Option<String>,
+
+    /// Flags to try, in order, instead of the built-in sequence. Empty means
+    /// use the built-in sequence.
+    pub flags: Vec<String>,
+
+    /// Argument lists to try, in order, each as one probe, e.g. `["version",
+    /// "--short"]`. When set, these are used instead of `flags`.
+    pub probes: Vec<Vec<String>>,
+
+    /// Arguments passed before the version flag, e.g. `-m pip` to probe
+    /// `python -m pip --version`. With these set, the built-in sequence is the
+    /// generic one, since the tool's own flags may not apply.
+    pub args: Vec<String>,
+
+    /// Kill a probe that hasn't exited after this long, e.g. an interpreter that
+    /// drops into a REPL. `None` waits forever.
+    pub timeout: Option<Duration>,
+
+    /// How the version is extracted from the probe output, e.g. which [`VersionScheme`].
+    pub extract: ExtractOptions,
+
+    /// `PATH` directories to leave out when searching for the executables to
+    /// probe; see [`find_executables_excluding`].
+    pub exclude_dirs: Vec<PathBuf>,
+
+    /// How many levels of subdirectories below each `PATH` directory are also
+    /// searched; see [`find_executables_recursive`]. `0` searches `PATH` only.
+    pub search_depth: usize,
+
+    /// Treat the command as a pattern such as `python3*` matching the names of
+    /// executables; see [`find_executables_matching_in`].
+    pub glob: bool,
+
+    /// The name the executable is run under instead of its path, e.g. `wget`
+    /// to probe that applet of a multicall binary like busybox. The built-in
+    /// flags are then those for this name.
+    #[cfg(unix)]
+    pub arg0: Option<String>,
+}
+
+impl Default for ProbeOptions {
+    /// The built-in behaviour, except that the timeout and flags are taken from
+    /// [`TIMEOUT_ENV_VAR`] and [`FLAGS_ENV_VAR`] when those are set.
+    fn default() -> Self {
+        Self {
+            stdin_input: None,
+            flags: env_flags(),
+            probes: Vec::new(),
+            args: Vec::new(),
+            timeout: Some(env_timeout().unwrap_or(DEFAULT_PROBE_TIMEOUT)),
+            extract: ExtractOptions::default(),
+            exclude_dirs: Vec::new(),
+            search_depth: 0,
+            glob: false,
+            #[cfg(unix)]
+            arg0: None,
+        }
+    }
+}
+
+fn env_timeout() -> Option<Duration> {
+    let value = std::env::var(TIMEOUT_ENV_VAR).ok()?;
+
+    match value.trim().parse() {
+        Ok(millis) => Some(Duration::from_millis(millis)),
+        Err(_) => {
+            log::warn!(
+                "Ignoring {}={:?}: not a number of milliseconds",
+                TIMEOUT_ENV_VAR,
+                value
+            );
+            None
+        }
+    }
+}
+
+fn env_flags() -> Vec<String> {
+    std::env::var(FLAGS_ENV_VAR)
+        .map(|flags| {
+            flags
+                .split(',')
+                .map(str::trim)
+                .filter(|flag| !flag.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn get_version(executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
+    get_version_with(executable_path, &ProbeOptions::default())
+}
+
+/// Like [`get_version`], but tries only `flags`, in order, e.g. `&["-version"]`.
+pub fn get_version_with_flag(
+    executable_path: &str,
+    flags: &[&str],
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let options = ProbeOptions {
+        flags: flags.iter().map(|flag| flag.to_string()).collect(),
+        ..Default::default()
+    };
+
+    get_version_with(executable_path, &options)
+}
+
+/// Like [`get_version`], but kills each probe that runs for longer than `timeout`.
+pub fn get_version_with_timeout(
+    executable_path: &str,
+    timeout: Duration,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let options = ProbeOptions {
+        timeout: Some(timeout),
+        ..Default::default()
+    };
+
+    get_version_with(executable_path, &options)
+}
+
+fn run_probe(
+    executable_path: &str,
+    probe: &[&str],
+    options: &ProbeOptions,
+) -> std::io::Result<Output> {
+    let mut command = Command::new(executable_path);
+    #[cfg(unix)]
+    if let Some(arg0) = &options.arg0 {
+        std::os::unix::process::CommandExt::arg0(&mut command, arg0);
+    }
+
+    command.args(&options.args).args(probe);
+
+    run_command(command, options)
+}
+
+/// Run `command` with the input and timeout from `options`, collecting its output.
+fn run_command(mut command: Command, options: &ProbeOptions) -> std::io::Result<Output> {
+    // Without input, stdin is null so a probe can never block waiting on the terminal.
+    let stdin = match options.stdin_input {
+        Some(_) => Stdio::piped(),
+        None => Stdio::null(),
+    };
+
+    let mut child = command
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let (Some(input), Some(mut stdin)) = (&options.stdin_input, child.stdin.take()) {
+        // A child that exits without reading its input is not an error.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let Some(timeout) = options.timeout else {
+        return child.wait_with_output();
+    };
+
+    // Drain both pipes on their own threads so a chatty child can't fill one
+    // and stall while we wait on it.
+    let stdout = child.stdout.take().map(read_to_end_in_background);
+    let stderr = child.stderr.take().map(read_to_end_in_background);
+    let deadline = Instant::now() + timeout;
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("no exit within {:?}", timeout),
+            ));
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let collect = |reader: Option<std::thread::JoinHandle<Vec<u8>>>| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    };
+
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+fn read_to_end_in_background<R>(mut reader: R) -> std::thread::JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+pub fn get_version_with(
+    executable_path: &str,
+    options: &ProbeOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    get_version_verbose_with(executable_path, options).map(|(info, _)| info)
+}
+
+/// Like [`get_version`], but also returns the combined stdout and stderr that
+/// the version was extracted from, e.g. to see why an odd banner parsed wrongly.
+pub fn get_version_verbose(
+    executable_path: &str,
+) -> Result<(ExecutableInfo, String), LatestVersionError> {
+    get_version_verbose_with(executable_path, &ProbeOptions::default())
+}
+
+/// Like [`get_version_verbose`], but probes according to `options`.
+pub fn get_version_verbose_with(
+    executable_path: &str,
+    options: &ProbeOptions,
+) -> Result<(ExecutableInfo, String), LatestVersionError> {
+    let probes = probes_for(executable_path, options);
+
+    probe_version_verbose(executable_path, &probes, &options.extract, |probe| {
+        run_probe(executable_path, probe, options).map(ProbeOutput::from)
+    })
+    .map(|(mut info, raw)| {
+        info.probe_command
+            .splice(1..1, options.args.iter().cloned());
+        (info, raw)
+    })
+}
+
+/// The argument lists [`get_version_with`] tries on `executable_path`, in
+/// order, not counting the leading `options.args`.
+fn probes_for<'a>(executable_path: &str, options: &'a ProbeOptions) -> Vec<Vec<&'a str>> {
+    if !options.probes.is_empty() {
+        return options
+            .probes
+            .iter()
+            .map(|probe| probe.iter().map(String::as_str).collect())
+            .collect();
+    }
+
+    let flags: Vec<&str> = if !options.flags.is_empty() {
+        options.flags.iter().map(String::as_str).collect()
+    } else if !options.args.is_empty() {
+        DEFAULT_VERSION_FLAGS.to_vec()
+    } else {
+        #[cfg(unix)]
+        let executable_path = options.arg0.as_deref().unwrap_or(executable_path);
+        version_flags_for(executable_path)
+    };
+    flags.into_iter().map(|flag| vec![flag]).collect()
+}
+
+/// An executable that would be probed, and the command lines that would be
+/// tried on it, in order, until one reports a version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlannedProbe {
+    pub path: String,
+    pub commands: Vec<Vec<String>>,
+}
+
+impl std::fmt::Display for PlannedProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.path)?;
+
+        for command in &self.commands {
+            writeln!(f, "  {}", command.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What looking up `command` with `options` would run, without running
+/// anything: every executable found, with the probes that would be tried on it.
+pub fn plan_probes(
+    command: &str,
+    options: &ProbeOptions,
+) -> Result<Vec<PlannedProbe>, LatestVersionError> {
+    let executables = find_executables_with(command, options)?;
+
+    Ok(executables
+        .into_iter()
+        .map(|path| {
+            let commands = probes_for(&path, options)
+                .into_iter()
+                .map(|probe| {
+                    std::iter::once(path.as_str())
+                        .chain(options.args.iter().map(String::as_str))
+                        .chain(probe)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .collect();
+            PlannedProbe { path, commands }
+        })
+        .collect())
+}
+
+/// Probe `executable_path args... flag`, for tools run through a wrapper
+/// such as `python -m pip --version` or `rustup run stable rustc --version`.
+pub fn get_version_args(
+    executable_path: &str,
+    args: &[&str],
+    flag: &str,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let options = ProbeOptions {
+        flags: vec![flag.to_string()],
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        ..Default::default()
+    };
+
+    get_version_with(executable_path, &options)
+}
+
+/// What a single probe printed, and how it exited.
+struct ProbeOutput {
+    stdout: String,
+    stderr: String,
+    /// `None` when the runner, like [`Provider::run`], doesn't report it.
+    status: Option<ExitStatus>,
+}
+
+impl ProbeOutput {
+    /// Output from a runner that only reports stdout and stderr mixed together.
+    fn combined(output: String) -> Self {
+        Self {
+            stdout: output,
+            stderr: String::new(),
+            status: None,
+        }
+    }
+}
+
+impl From<Output> for ProbeOutput {
+    fn from(output: Output) -> Self {
+        Self {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            status: Some(output.status),
+        }
+    }
+}
+
+/// Try each of `flags` in turn, using `run` to get the combined output of
+/// `executable_path <flag>` and `extract` to find the version in it.
+fn probe_version<F>(
+    executable_path: &str,
+    flags: &[&str],
+    extract: &ExtractOptions,
+    mut run: F,
+) -> Result<ExecutableInfo, LatestVersionError>
+where
+    F: FnMut(&str) -> std::io::Result<String>,
+{
+    let probes: Vec<Vec<&str>> = flags.iter().map(|&flag| vec![flag]).collect();
+
+    // Each probe is a single flag, so `probe[0]` always exists.
+    probe_version_verbose(executable_path, &probes, extract, |probe| {
+        run(probe[0]).map(ProbeOutput::combined)
+    })
+    .map(|(info, _)| info)
+}
+
+/// Like [`probe_version`], but also returns the output the version came from,
+/// and each of `probes` is a whole argument list, such as `["version", "--short"]`.
+fn probe_version_verbose<F>(
+    executable_path: &str,
+    probes: &[Vec<&str>],
+    extract: &ExtractOptions,
+    mut run: F,
+) -> Result<(ExecutableInfo, String), LatestVersionError>
+where
+    F: FnMut(&[&str]) -> std::io::Result<ProbeOutput>,
+{
+    let mut unversioned = Unversioned::default();
+
+    for (index, probe) in probes.iter().enumerate() {
+        let output = run(probe);
+        if let Some(result) = probe_outcome(
+            executable_path,
+            index,
+            probe,
+            output,
+            extract,
+            &mut unversioned,
+        ) {
+            return result;
+        }
+    }
+
+    Err(no_version_found(executable_path, unversioned))
+}
+
+/// What the output of the `index`th probe, `executable_path args...`, settles:
+/// `None` if the next probe should be tried, having kept what it printed in
+/// `unversioned` for the error if none of them has a version.
+///
+/// Plenty of tools print their version and then exit non-zero, or print it
+/// only to stderr, so the exit status is logged but never required, and stdout
+/// and stderr are each searched in turn. Searching them joined together comes
+/// last, since a number ending one could otherwise run into one starting the other.
+fn probe_outcome(
+    executable_path: &str,
+    index: usize,
+    args: &[&str],
+    output: std::io::Result<ProbeOutput>,
+    extract: &ExtractOptions,
+    unversioned: &mut Unversioned,
+) -> Option<Result<(ExecutableInfo, String), LatestVersionError>> {
+    let flag = args.join(" ");
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+            return Some(Err(LatestVersionError::Timeout(
+                executable_path.to_string(),
+            )))
+        }
+        Err(e) if index == 0 => {
+            return Some(Err(LatestVersionError::CommandExecutionError(
+                executable_path.to_string(),
+                e,
+            )))
+        }
+        Err(e) => {
+            log::debug!("Failed to run {} {}: {}", executable_path, flag, e);
+            return None;
+        }
+    };
+
+    if let Some(status) = output.status.filter(|status| !status.success()) {
+        log::debug!("{} {} exited with {}", executable_path, flag, status);
+    }
+
+    let stdout = normalize_newlines(&output.stdout);
+    let stderr = normalize_newlines(&output.stderr);
+    let combined_output = format!("{}{}", stdout, stderr);
+    let version = extract_version_with(&stdout, extract)
+        .or_else(|| extract_version_with(&stderr, extract))
+        .or_else(|| extract_version_with(&combined_output, extract));
+
+    if let Some(version_str) = version {
+        log::debug!("{} {} reported {}", executable_path, flag, version_str);
+        let info = ExecutableInfo {
+            path: executable_path.to_string(),
+            version: version_str,
+            probe_command: std::iter::once(executable_path)
+                .chain(args.iter().copied())
+                .map(str::to_string)
+                .collect(),
+        };
+        return Some(Ok((info, combined_output)));
+    }
+
+    log::debug!(
+        "No version in the output of {} {}: {:?}",
+        executable_path,
+        flag,
+        combined_output
+    );
+    if combined_output.trim().is_empty() {
+        if let Some(status) = output.status.filter(|status| !status.success()) {
+            unversioned.silent_failure.get_or_insert(status.to_string());
+        }
+    } else {
+        unversioned.printed = true;
+    }
+    unversioned.output.get_or_insert(combined_output);
+    None
+}
+
+/// `output` without a leading byte order mark and with `\r\n` line endings
+/// turned into `\n`, as some Windows tools print them, so anchored patterns
+/// see the same text on every platform.
+fn normalize_newlines(output: &str) -> String {
+    let output = output.strip_prefix('\u{feff}').unwrap_or(output);
+
+    output.replace("\r\n", "\n")
+}
+
+/// What the probes of an executable that found no version printed.
+#[derive(Debug, Default)]
+struct Unversioned {
+    /// The first output without a version in it.
+    output: Option<String>,
+    /// How the first probe that printed nothing at all failed, if one did.
+    silent_failure: Option<String>,
+    /// Whether any probe printed anything.
+    printed: bool,
+}
+
+fn no_version_found(executable_path: &str, unversioned: Unversioned) -> LatestVersionError {
+    if let (Some(status), false) = (&unversioned.silent_failure, unversioned.printed) {
+        return LatestVersionError::SilentFailure {
+            path: executable_path.to_string(),
+            status: status.clone(),
+        };
+    }
+
+    match unversioned.output {
+        Some(output) => LatestVersionError::NoVersionInOutput {
+            path: executable_path.to_string(),
+            output,
+        },
+        None => {
+            LatestVersionError::VersionExtractionError("No version information found".to_string())
+        }
+    }
+}
+
+/// Options controlling how [`find_latest_version_with`] picks a winner.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionOptions {
+    /// Fail with [`LatestVersionError::AmbiguousLatest`] when more than one path
+    /// shares the latest version.
+    pub fail_on_tie: bool,
+
+    /// Ignore pre-release versions such as `2.0.0-rc1`, selecting the newest stable one.
+    pub exclude_prereleases: bool,
+
+    /// Reject versions that aren't strict semver (and so would need the flexible
+    /// comparison) unless they have at least this many numeric components.
+    /// `0` accepts everything.
+    pub min_fuzzy_components: usize,
+
+    /// Select the oldest version instead of the newest.
+    pub oldest: bool,
+
+    /// Directories in priority order for breaking ties: of two executables with
+    /// the same version, the one in the earlier directory wins. Executables in
+    /// none of them rank below those that are, and among themselves keep `PATH` order.
+    pub prefer_dirs: Vec<PathBuf>,
+
+    /// Rank versions with [`compare_versions_partial`], so a component a tool
+    /// didn't report counts as unknown rather than zero. Only versions
+    /// extracted with [`NormalizePolicy::Exact`] still say which ones those are.
+    pub partial_versions: bool,
+}
+
+impl SelectionOptions {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        if self.partial_versions {
+            compare_versions_partial(a, b)
+        } else {
+            compare_versions(a, b)
+        }
+    }
+
+    fn same_version(&self, a: &str, b: &str) -> bool {
+        if self.partial_versions {
+            compare_versions_partial(a, b) == Ordering::Equal
+        } else {
+            same_version(a, b)
+        }
+    }
+}
+
+pub fn find_latest_version(
+    info_list: Vec<ExecutableInfo>,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    find_latest_version_with(info_list, &SelectionOptions::default())
+}
+
+/// Like [`find_latest_version`], but selects the oldest version, e.g. to
+/// reproduce a bug on the minimum supported toolchain.
+pub fn find_oldest_version(
+    info_list: Vec<ExecutableInfo>,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let options = SelectionOptions {
+        oldest: true,
+        ..Default::default()
+    };
+
+    find_latest_version_with(info_list, &options)
+}
+
+pub fn find_latest_version_with(
+    info_list: Vec<ExecutableInfo>,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let info_list: Vec<ExecutableInfo> = info_list
+        .into_iter()
+        .filter(|info| {
+            let comparable = is_comparable(&info.version);
+            if !comparable {
+                log::debug!(
+                    "Ignoring {}: {:?} is not a version",
+                    info.path,
+                    info.version
+                );
+            }
+            comparable
+        })
+        .filter(|info| !(options.exclude_prereleases && is_prerelease(&info.version)))
+        .filter(|info| {
+            Version::parse(&info.version).is_ok()
+                || numeric_components(&info.version) >= options.min_fuzzy_components
+        })
+        .collect();
+
+    // With `oldest`, the same walk keeps whichever version compares lowest instead.
+    let wanted = if options.oldest {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    };
+
+    let preferred = canonical_dirs(&options.prefer_dirs);
+    let priority = |info: &ExecutableInfo| {
+        std::path::Path::new(&info.path)
+            .parent()
+            .and_then(|parent| {
+                preferred
+                    .iter()
+                    .position(|dir| *dir == canonical_dir(parent))
+            })
+            .unwrap_or(preferred.len())
+    };
+
+    let mut latest_info: Option<&ExecutableInfo> = None;
+
+    for info in &info_list {
+        if Version::parse(&info.version).is_err() {
+            log::debug!(
+                "{} ({}) is not semver; using the flexible comparison",
+                info.path,
+                info.version
+            );
+        }
+
+        match latest_info {
+            None => latest_info = Some(info),
+            Some(latest) => {
+                if options.compare(&info.version, &latest.version) == wanted
+                    || (options.same_version(&info.version, &latest.version)
+                        && priority(info) < priority(latest))
+                {
+                    latest_info = Some(info);
+                }
+            }
+        }
+    }
+
+    let latest = latest_info.ok_or(LatestVersionError::VersionExtractionError(
+        "No valid versions found".to_string(),
+    ))?;
+    log::info!("Selected {} ({})", latest.path, latest.version);
+
+    let tied = tied_with(&info_list, latest, options);
+    if tied.len() > 1 {
+        let paths: Vec<String> = tied.iter().map(|info| info.path.clone()).collect();
+        if options.fail_on_tie {
+            return Err(LatestVersionError::AmbiguousLatest(paths));
+        }
+        log::warn!(
+            "{} is reported by more than one executable: {}",
+            latest.version,
+            paths.join(", ")
+        );
+    }
+
+    Ok(latest.clone())
+}
+
+/// Like [`find_latest_version_with`], but also returns every other executable
+/// reporting the same version as the selected one, in order, e.g. two copies
+/// of one release shadowing each other on `PATH`.
+pub fn find_latest_with_conflicts(
+    info_list: Vec<ExecutableInfo>,
+    options: &SelectionOptions,
+) -> Result<(ExecutableInfo, Vec<ExecutableInfo>), LatestVersionError> {
+    let latest = find_latest_version_with(info_list.clone(), options)?;
+    let conflicts = tied_with(&info_list, &latest, options)
+        .into_iter()
+        .filter(|info| info.path != latest.path)
+        .cloned()
+        .collect();
+
+    Ok((latest, conflicts))
+}
+
+/// The executables in `info_list` with the same version as `latest`, one per path.
+fn tied_with<'a>(
+    info_list: &'a [ExecutableInfo],
+    latest: &ExecutableInfo,
+    options: &SelectionOptions,
+) -> Vec<&'a ExecutableInfo> {
+    let mut tied: Vec<&ExecutableInfo> = Vec::new();
+
+    for info in info_list {
+        if options.same_version(&info.version, &latest.version)
+            && !tied.iter().any(|known| known.path == info.path)
+        {
+            tied.push(info);
+        }
+    }
+
+    tied
+}
+
+/// Whether `version` can be ranked at all: strict semver, or something with a
+/// number in it that the flexible comparison understands. `unknown` is neither.
+fn is_comparable(version: &str) -> bool {
+    Version::parse(version).is_ok()
+        || (numeric_components(version) > 0 && version_compare::Version::from(version).is_some())
+}
+
+fn numeric_components(version: &str) -> usize {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .count()
+}
+
+fn is_prerelease(version: &str) -> bool {
+    Version::parse(version).is_ok_and(|version| !version.pre.is_empty())
+}
+
+fn same_version(a: &str, b: &str) -> bool {
+    cmp_versions(a, b) == Some(Ordering::Equal)
+}
+
+/// Order two version strings the way the crate ranks them: strictly as semver
+/// where both parse, and with a flexible comparison otherwise, so that e.g.
+/// `1.8.0_292` sorts below `1.8.0_301`. Versions that can't be compared at all
+/// are treated as equal.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    cmp_versions(a, b).unwrap_or(Ordering::Equal)
+}
+
+/// Like [`compare_versions`], but for versions kept as reported with
+/// [`NormalizePolicy::Exact`]: a component left out is unknown rather than
+/// zero, so `3.11` ranks below both `3.11.0` and `3.11.2`, which are known to
+/// be at least that new, instead of equal to the first.
+pub fn compare_versions_partial(a: &str, b: &str) -> Ordering {
+    match compare_versions(a, b) {
+        Ordering::Equal => numeric_components(a).cmp(&numeric_components(b)),
+        ordering => ordering,
+    }
+}
+
+/// Like [`compare_versions`], but `None` if the versions can't be compared at all.
+fn cmp_versions(a: &str, b: &str) -> Option<Ordering> {
+    match (Version::parse(a), Version::parse(b)) {
+        (Ok(a), Ok(b)) => Some(a.cmp(&b)),
+        _ => match version_compare::compare(a, b) {
+            Ok(Cmp::Gt) => Some(Ordering::Greater),
+            Ok(Cmp::Lt) => Some(Ordering::Less),
+            Ok(Cmp::Eq) => Some(Ordering::Equal),
+            _ => None,
+        },
+    }
+}
+
+/// Probe two known executables and order them by version, without scanning PATH.
+///
+/// The ordering is that of `a` relative to `b`; versions that can't be compared
+/// are treated as equal.
+pub fn compare_paths(
+    a: &str,
+    b: &str,
+) -> Result<(ExecutableInfo, ExecutableInfo, Ordering), LatestVersionError> {
+    compare_paths_with(a, b, &ProbeOptions::default())
+}
+
+/// Like [`compare_paths`], but probes both executables according to `options`.
+pub fn compare_paths_with(
+    a: &str,
+    b: &str,
+    options: &ProbeOptions,
+) -> Result<(ExecutableInfo, ExecutableInfo, Ordering), LatestVersionError> {
+    let info_a = get_version_with(a, options)?;
+    let info_b = get_version_with(b, options)?;
+    let ordering = compare_versions(&info_a.version, &info_b.version);
+
+    Ok((info_a, info_b, ordering))
+}
+
+pub fn find_latest_command(command: &str) -> Result<ExecutableInfo, LatestVersionError> {
+    VersionFinder::new().latest(command)
+}
+
+/// Like [`find_latest_command`], but picks the winner according to `options`.
+pub fn find_latest_command_with(
+    command: &str,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    VersionFinder::from_options(&ProbeOptions::default(), options).latest(command)
+}
+
+/// Like [`find_latest_command_with`], but probes each executable according to `probe`.
+pub fn find_latest_command_probed(
+    command: &str,
+    probe: &ProbeOptions,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    VersionFinder::from_options(probe, options).latest(command)
+}
+
+/// [`find_latest_command`] for each of `commands`, in order. A failure is
+/// reported alongside its command and doesn't stop the rest from being probed.
+pub fn find_latest_commands(
+    commands: &[&str],
+) -> Vec<(String, Result<ExecutableInfo, LatestVersionError>)> {
+    commands
+        .iter()
+        .map(|command| (command.to_string(), find_latest_command(command)))
+        .collect()
+}
+
+/// The path and strictly-typed version of the latest `command`, failing if that
+/// version isn't valid semver.
+pub fn find_latest_command_semver(command: &str) -> Result<(String, Version), LatestVersionError> {
+    let info = find_latest_command(command)?;
+    let version = info.parsed()?;
+
+    Ok((info.path, version))
+}
+
+/// The latest version of `command`, or `default` if it isn't installed or reports no version.
+pub fn version_or(command: &str, default: &str) -> String {
+    find_latest_command(command)
+        .map(|info| info.version)
+        .unwrap_or_else(|_| default.to_string())
+}
+
+/// Like [`find_latest_command`], but probes through a [`VersionCache`].
+#[cfg(feature = "cache")]
+pub fn find_latest_command_cached(
+    command: &str,
+    cache: &mut VersionCache,
+    probe: &ProbeOptions,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    let executables = find_executables_with(command, probe)?;
+    let results = cache::get_versions_cached_with(&executables, cache, probe);
+    let (info_list, _) = split_results(executables, results);
+
+    select_latest(command, info_list, options)
+}
+
+fn probe_latest<F>(
+    command: &str,
+    executables: Vec<String>,
+    mut probe: F,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError>
+where
+    F: FnMut(&str) -> Result<ExecutableInfo, LatestVersionError>,
+{
+    let (info_list, _) = probe_serially(executables, &mut probe);
+
+    select_latest(command, info_list, options)
+}
+
+fn select_latest(
+    command: &str,
+    info_list: Vec<ExecutableInfo>,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    if info_list.is_empty() {
+        return Err(LatestVersionError::VersionExtractionError(format!(
+            "No version information found for command '{}'",
+            command
+        )));
+    }
+
+    find_latest_version_with(info_list, options)
+}
+
+/// Probe every executable, splitting the successes from the failures.
+fn probe_serially<F>(
+    executables: Vec<String>,
+    mut probe: F,
+) -> (Vec<ExecutableInfo>, Vec<(String, LatestVersionError)>)
+where
+    F: FnMut(&str) -> Result<ExecutableInfo, LatestVersionError>,
+{
+    let results = executables
+        .iter()
+        .map(|executable| probe(executable))
+        .collect();
+
+    split_results(executables, results)
+}
+
+/// Like [`probe_serially`], but probes on a thread per available CPU.
+///
+/// Results keep the order of `executables`, so ties still go to the earlier PATH entry.
+fn probe_executables<F>(
+    executables: Vec<String>,
+    probe: F,
+) -> (Vec<ExecutableInfo>, Vec<(String, LatestVersionError)>)
+where
+    F: Fn(&str) -> Result<ExecutableInfo, LatestVersionError> + Sync,
+{
+    let results = probe_in_parallel(&executables, probe);
+
+    split_results(executables, results)
+}
+
+/// The result of probing each of `executables`, in the same order.
+fn probe_in_parallel<T, F>(executables: &[String], probe: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    let workers = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(executables.len())
+        .max(1);
+    let chunk_size = executables.len().div_ceil(workers).max(1);
+    let probe = &probe;
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = executables
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|executable| probe(executable))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+fn split_results(
+    executables: Vec<String>,
+    results: Vec<Result<ExecutableInfo, LatestVersionError>>,
+) -> (Vec<ExecutableInfo>, Vec<(String, LatestVersionError)>) {
+    let mut info_list = Vec::new();
+    let mut failures = Vec::new();
+
+    for (executable, result) in executables.into_iter().zip(results) {
+        match result {
+            Ok(info) => info_list.push(info),
+            Err(e) => {
+                log::warn!("Skipping {}: {}", executable, e);
+                failures.push((executable, e));
+            }
+        }
+    }
+
+    (info_list, failures)
+}
+
+/// The outcome of [`find_latest_command_detailed`].
+#[derive(Debug)]
+pub struct LatestReport {
+    /// The latest version found, if any executable reported one.
+    pub latest: Option<ExecutableInfo>,
+    /// Executables whose version couldn't be probed, with the reason.
+    pub failures: Vec<(String, LatestVersionError)>,
+}
+
+/// Like [`find_latest_command`], but also reports the executables that failed to probe.
+pub fn find_latest_command_detailed(command: &str) -> Result<LatestReport, LatestVersionError> {
+    let executables = find_executables(command)?;
+    let (info_list, failures) = probe_executables(executables, get_version);
+
+    let latest = if info_list.is_empty() {
+        None
+    } else {
+        Some(find_latest_version(info_list)?)
+    };
+
+    Ok(LatestReport { latest, failures })
+}
+
+/// What probing one executable in [`probe_all`] found.
+#[derive(Debug)]
+pub struct ProbeResult {
+    pub path: String,
+    /// The version, or why it couldn't be read.
+    pub version: Result<String, LatestVersionError>,
+}
+
+/// Every executable for `command`, in PATH order, with its version or the
+/// reason it has none. Empty if `command` isn't found at all.
+pub fn probe_all(command: &str) -> Vec<ProbeResult> {
+    probe_all_with(command, &ProbeOptions::default()).unwrap_or_default()
+}
+
+/// Like [`probe_all`], but probes according to `options`, and fails with
+/// [`LatestVersionError::CommandNotFound`] if there is nothing to probe.
+pub fn probe_all_with(
+    command: &str,
+    options: &ProbeOptions,
+) -> Result<Vec<ProbeResult>, LatestVersionError> {
+    let executables = find_executables_with(command, options)?;
+    let results = probe_in_parallel(&executables, |executable| {
+        get_version_with(executable, options)
+    });
+
+    Ok(executables
+        .into_iter()
+        .zip(results)
+        .map(|(path, result)| ProbeResult {
+            path,
+            version: result.map(|info| info.version),
+        })
+        .collect())
+}
+
+/// Every executable for `command` whose version could be probed, in PATH order.
+pub fn find_all_versions(command: &str) -> Result<Vec<ExecutableInfo>, LatestVersionError> {
+    VersionFinder::new().all(command)
+}
+
+/// Like [`find_all_versions`], but probes each executable according to `options`.
+pub fn find_all_versions_with(
+    command: &str,
+    options: &ProbeOptions,
+) -> Result<Vec<ExecutableInfo>, LatestVersionError> {
+    VersionFinder::from_options(options, &SelectionOptions::default()).all(command)
+}
+
+/// Like [`find_all_versions_with`], but with the raw output each version was
+/// read from, as [`get_version_verbose_with`] returns it.
+pub fn find_all_versions_verbose_with(
+    command: &str,
+    options: &ProbeOptions,
+) -> Result<Vec<(ExecutableInfo, String)>, LatestVersionError> {
+    let executables = find_executables_with(command, options)?;
+    let results = probe_in_parallel(&executables, |executable| {
+        get_version_verbose_with(executable, options)
+    });
+
+    Ok(executables
+        .iter()
+        .zip(results)
+        .filter_map(|(executable, result)| match result {
+            Ok(found) => Some(found),
+            Err(e) => {
+                log::warn!("Skipping {}: {}", executable, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Like [`find_latest_command_probed`], but with the raw output the selected
+/// version was read from, so it need not be probed again to show it.
+pub fn find_latest_command_verbose_with(
+    command: &str,
+    probe: &ProbeOptions,
+    options: &SelectionOptions,
+) -> Result<(ExecutableInfo, String), LatestVersionError> {
+    let probed = find_all_versions_verbose_with(command, probe)?;
+    let info_list = probed.iter().map(|(info, _)| info.clone()).collect();
+    let latest = select_latest(command, info_list, options)?;
+    let raw = probed
+        .into_iter()
+        .find(|(info, _)| info.path == latest.path)
+        .map(|(_, raw)| raw)
+        .unwrap_or_default();
+
+    Ok((latest, raw))
+}
+
+/// Like [`find_all_versions_with`], but calls `on_found` with each executable
+/// as soon as it has been probed; see [`VersionFinder::stream`].
+pub fn find_all_versions_streamed<F>(
+    command: &str,
+    options: &ProbeOptions,
+    on_found: F,
+) -> Result<Vec<ExecutableInfo>, LatestVersionError>
+where
+    F: Fn(&ExecutableInfo) + Sync,
+{
+    VersionFinder::from_options(options, &SelectionOptions::default()).stream(command, on_found)
+}
+
+/// Every executable for `command` whose version satisfies `requirement`, newest first.
+///
+/// Versions that aren't valid semver can't be checked against a requirement and are left out.
+pub fn find_matching(
+    command: &str,
+    requirement: &semver::VersionReq,
+) -> Result<Vec<ExecutableInfo>, LatestVersionError> {
+    Ok(select_matching(find_all_versions(command)?, requirement))
+}
+
+/// Whether any executable for `command` reports a version satisfying
+/// `requirement`, e.g. `~3.11` for "any 3.11.x". Fails with
+/// [`LatestVersionError::CommandNotFound`] if there is no `command` at all,
+/// rather than returning `false`.
+pub fn is_installed(
+    command: &str,
+    requirement: &semver::VersionReq,
+) -> Result<bool, LatestVersionError> {
+    is_installed_with(command, requirement, &ProbeOptions::default())
+}
+
+/// Like [`is_installed`], but probes according to `options`.
+pub fn is_installed_with(
+    command: &str,
+    requirement: &semver::VersionReq,
+    options: &ProbeOptions,
+) -> Result<bool, LatestVersionError> {
+    let info_list = find_all_versions_with(command, options)?;
+
+    Ok(!select_matching(info_list, requirement).is_empty())
+}
+
+/// The entries of `info_list` whose version satisfies `requirement`, newest first.
+pub fn select_matching(
+    info_list: Vec<ExecutableInfo>,
+    requirement: &semver::VersionReq,
+) -> Vec<ExecutableInfo> {
+    let mut matching: Vec<ExecutableInfo> = info_list
+        .into_iter()
+        .filter(|info| {
+            info.parsed()
+                .is_ok_and(|version| requirement.matches(&version))
+        })
+        .collect();
+
+    sort_executables(&mut matching, SortOrder::Version);
+    matching
+}
+
+/// The executables for `command` that are strictly newer than the first one on
+/// PATH, which is the one a shell would run.
+///
+/// An empty result means the shell already picks the newest. Executables that
+/// can't be probed are skipped, including a first PATH match that reports no version.
+pub fn candidates_newer_than_first(
+    command: &str,
+) -> Result<Vec<ExecutableInfo>, LatestVersionError> {
+    Ok(newer_than_first(find_all_versions(command)?))
+}
+
+fn newer_than_first(info_list: Vec<ExecutableInfo>) -> Vec<ExecutableInfo> {
+    let mut info_list = info_list.into_iter();
+
+    let Some(first) = info_list.next() else {
+        return Vec::new();
+    };
+
+    info_list
+        .filter(|info| cmp_versions(&info.version, &first.version) == Some(Ordering::Greater))
+        .collect()
+}
+
+/// How [`sort_executables`] orders a list of executables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Newest version first; equal versions keep their PATH order.
+    #[default]
+    Version,
+    /// Lexicographically by path, for output that is stable across machines.
+    Path,
+}
+
+pub fn sort_executables(info_list: &mut [ExecutableInfo], order: SortOrder) {
+    match order {
+        SortOrder::Version => info_list.sort_by(|a, b| compare_versions(&b.version, &a.version)),
+        SortOrder::Path => info_list.sort_by(|a, b| a.path.cmp(&b.path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write an executable shell script named `name` into `dir`.
+    #[cfg(unix)]
+    pub(crate) fn write_script(
+        dir: &std::path::Path,
+        name: &str,
+        body: &str,
+    ) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_semantic_version_parsing() {
+        let output = "Python 3.11.4";
+        let version = extract_version(output);
+        assert_eq!(version, Some("3.11.4".to_string()));
+    }
+
+    #[test]
+    fn test_major_minor_version_parsing() {
+        let output = "Node.js v18.16.0";
+        let version = extract_version(output);
+        assert_eq!(version, Some("18.16.0".to_string()));
+    }
+
+    #[test]
+    fn test_major_version_parsing() {
+        let output = "Git version 2";
+        let version = extract_version(output);
+        assert_eq!(version, Some("2.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        for (pattern, name) in [
+            ("python*", "python"),
+            ("python*", "python3.11"),
+            ("*3.1?", "python3.10"),
+            ("p*n*1", "python3.11"),
+            ("*", ""),
+        ] {
+            assert!(glob_matches(pattern, name), "{} {}", pattern, name);
+        }
+        for (pattern, name) in [
+            ("python*", "ipython3"),
+            ("python3.1?", "python3.1"),
+            ("python", "python3"),
+            ("*.exe", "python3"),
+        ] {
+            assert!(!glob_matches(pattern, name), "{} {}", pattern, name);
+        }
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let errors = [
+            (
+                LatestVersionError::CommandNotFound("tool".into()),
+                "command-not-found",
+            ),
+            (
+                LatestVersionError::CommandExecutionError(
+                    "tool".into(),
+                    std::io::ErrorKind::PermissionDenied.into(),
+                ),
+                "command-execution-failed",
+            ),
+            (
+                LatestVersionError::VersionExtractionError("none".into()),
+                "version-extraction-failed",
+            ),
+            (
+                LatestVersionError::NoVersionInOutput {
+                    path: "tool".into(),
+                    output: "usage".into(),
+                },
+                "no-version-in-output",
+            ),
+            (
+                LatestVersionError::SilentFailure {
+                    path: "tool".into(),
+                    status: "exit status: 1".into(),
+                },
+                "silent-failure",
+            ),
+            (
+                LatestVersionError::PathFindingError("unset".into()),
+                "path-finding-failed",
+            ),
+            (
+                LatestVersionError::VersionParsingError(Version::parse("x").unwrap_err()),
+                "version-parsing-failed",
+            ),
+            (LatestVersionError::CacheError("io".into()), "cache-error"),
+            (
+                LatestVersionError::AmbiguousLatest(vec!["a".into(), "b".into()]),
+                "ambiguous-latest",
+            ),
+            (LatestVersionError::Timeout("tool".into()), "timeout"),
+            (
+                LatestVersionError::RegistryError("404".into()),
+                "registry-lookup-failed",
+            ),
+            (
+                LatestVersionError::NoMatchingVersion {
+                    command: "tool".into(),
+                    requirement: ">=2".into(),
+                },
+                "no-matching-version",
+            ),
+        ];
+
+        for (error, code) in errors {
+            assert_eq!(error.code(), code, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_crate_version_matches_the_manifest() {
+        let manifest = include_str!("../Cargo.toml");
+        let version_line = manifest
+            .lines()
+            .find(|line| line.starts_with("version = "))
+            .unwrap();
+        assert_eq!(version_line, format!("version = \"{}\"", VERSION));
+        assert!(Version::parse(VERSION).is_ok());
+    }
+
+    #[test]
+    fn test_version_components() {
+        let info = ExecutableInfo::new("/usr/bin/python3", "3.11.4");
+        assert_eq!(info.major(), Some(3));
+        assert_eq!(info.minor(), Some(11));
+        assert_eq!(info.patch(), Some(4));
+        assert_eq!(info.version, "3.11.4");
+
+        assert_eq!(info.dir(), Some(std::path::Path::new("/usr/bin")));
+
+        let info = ExecutableInfo::new("tool", "unknown");
+        assert_eq!(info.dir(), None);
+        assert_eq!(info.major(), None);
+        assert_eq!(info.minor(), None);
+        assert_eq!(info.patch(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_executable_info_round_trips_through_json() {
+        let info = ExecutableInfo {
+            path: "/usr/bin/python3".to_string(),
+            version: "3.11.4".to_string(),
+            probe_command: vec!["/usr/bin/python3".to_string(), "--version".to_string()],
+        };
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["path"], "/usr/bin/python3");
+        assert_eq!(json["version"], "3.11.4");
+
+        let round_tripped: ExecutableInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(round_tripped, info);
+
+        let without_probe: ExecutableInfo =
+            serde_json::from_str(r#"{"path": "/usr/bin/python3", "version": "3.11.4"}"#).unwrap();
+        assert_eq!(
+            without_probe,
+            ExecutableInfo::new("/usr/bin/python3", "3.11.4")
+        );
+    }
+
+    #[test]
+    fn test_version_comparison() {
+        let info1 = ExecutableInfo::new("/usr/bin/python3", "3.10.0");
+
+        let info2 = ExecutableInfo::new("/usr/local/bin/python3", "3.11.0");
+
+        let latest = find_latest_version(vec![info1, info2]).unwrap();
+        assert_eq!(latest.path, "/usr/local/bin/python3");
+        assert_eq!(latest.version, "3.11.0");
+    }
+
+    #[test]
+    fn test_fallback_version_comparison() {
+        let info1 = ExecutableInfo::new("/usr/bin/java", "1.8.0_302");
+
+        let info2 = ExecutableInfo::new("/usr/local/bin/java", "11.0.16");
+
+        let latest = find_latest_version(vec![info1, info2]).unwrap();
+        assert_eq!(latest.path, "/usr/local/bin/java");
+        assert_eq!(latest.version, "11.0.16");
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        // Both semver: pre-releases sort below their release.
+        assert_eq!(compare_versions("3.11.0", "3.10.2"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0.0-rc1", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.3", "1.2.3"), Ordering::Equal);
+
+        // Semver against a non-semver string falls back to the flexible comparison.
+        assert_eq!(compare_versions("11.0.16", "1.8.0_302"), Ordering::Greater);
+        assert_eq!(compare_versions("3.9", "3.10.0"), Ordering::Less);
+
+        // Neither is semver, including Java update numbers.
+        assert_eq!(compare_versions("1.8.0_251", "1.8.0_302"), Ordering::Less);
+        assert_eq!(compare_versions("2024.01", "2023.12"), Ordering::Greater);
+
+        // Padded with a zero, a reported 3.11 is indistinguishable from 3.11.0.
+        assert_eq!(compare_versions("3.11", "3.11.0"), Ordering::Equal);
+        assert_eq!(compare_versions_partial("3.11", "3.11.0"), Ordering::Less);
+        assert_eq!(compare_versions_partial("3.11", "3.11.2"), Ordering::Less);
+        assert_eq!(
+            compare_versions_partial("3.11.2", "3.11"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_versions_partial("3.12", "3.11.2"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_versions_partial("3.11.2", "3.11.2"),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_oldest_version_comparison() {
+        let info1 = ExecutableInfo::new("/usr/bin/python3", "3.10.0");
+
+        let info2 = ExecutableInfo::new("/usr/local/bin/python3", "3.11.0");
+
+        let oldest = find_oldest_version(vec![info2, info1]).unwrap();
+        assert_eq!(oldest.path, "/usr/bin/python3");
+        assert_eq!(oldest.version, "3.10.0");
+    }
+
+    #[test]
+    fn test_oldest_fallback_version_comparison() {
+        let info1 = ExecutableInfo::new("/usr/bin/java", "1.8.0_302");
+
+        let info2 = ExecutableInfo::new("/usr/local/bin/java", "11.0.16");
+
+        for info_list in [
+            vec![info1.clone(), info2.clone()],
+            vec![info2.clone(), info1.clone()],
+        ] {
+            let oldest = find_oldest_version(info_list).unwrap();
+            assert_eq!(oldest.path, "/usr/bin/java");
+            assert_eq!(oldest.version, "1.8.0_302");
+        }
+    }
+
+    #[test]
+    fn test_fail_on_tie() {
+        let info1 = ExecutableInfo::new("/usr/bin/python3", "3.11.0");
+
+        let info2 = ExecutableInfo::new("/usr/local/bin/python3", "3.11.0");
+
+        let info3 = ExecutableInfo::new("/opt/bin/python3", "3.10.0");
+
+        let info_list = vec![info1, info2, info3];
+
+        let latest = find_latest_version(info_list.clone()).unwrap();
+        assert_eq!(latest.path, "/usr/bin/python3");
+
+        let options = SelectionOptions {
+            fail_on_tie: true,
+            ..Default::default()
+        };
+        match find_latest_version_with(info_list, &options) {
+            Err(LatestVersionError::AmbiguousLatest(paths)) => {
+                assert_eq!(paths, ["/usr/bin/python3", "/usr/local/bin/python3"]);
+            }
+            other => panic!("expected AmbiguousLatest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unparseable_versions_are_ignored() {
+        for info_list in [
+            vec![
+                ExecutableInfo::new("/opt/bin/tool", "unknown"),
+                ExecutableInfo::new("/usr/bin/tool", "1.2.0"),
+            ],
+            vec![
+                ExecutableInfo::new("/usr/bin/tool", "1.2.0"),
+                ExecutableInfo::new("/opt/bin/tool", "unknown"),
+            ],
+        ] {
+            assert_eq!(
+                find_latest_version(info_list).unwrap().path,
+                "/usr/bin/tool"
+            );
+        }
+
+        let garbage = vec![
+            ExecutableInfo::new("/opt/bin/tool", "unknown"),
+            ExecutableInfo::new("/usr/bin/tool", ""),
+        ];
+        assert!(matches!(
+            find_latest_version(garbage),
+            Err(LatestVersionError::VersionExtractionError(_))
+        ));
+    }
+
+    #[test]
+    fn test_preferred_dirs_break_ties() {
+        let info_list = vec![
+            ExecutableInfo::new("/usr/bin/tool", "2.1.0"),
+            ExecutableInfo::new("/opt/tools/tool", "2.1.0"),
+            ExecutableInfo::new("/usr/local/bin/tool", "2.0.0"),
+        ];
+        assert_eq!(
+            find_latest_version(info_list.clone()).unwrap().path,
+            "/usr/bin/tool"
+        );
+
+        let options = SelectionOptions {
+            prefer_dirs: vec![PathBuf::from("/usr/local/bin"), PathBuf::from("/opt/tools")],
+            ..Default::default()
+        };
+        // A preferred directory only wins among equal versions.
+        assert_eq!(
+            find_latest_version_with(info_list, &options).unwrap().path,
+            "/opt/tools/tool"
+        );
+    }
+
+    #[test]
+    fn test_partial_versions_rank_below_explicit_ones() {
+        let exact = ExtractOptions {
+            normalize: NormalizePolicy::Exact,
+            ..Default::default()
+        };
+        let info_list = vec![
+            ExecutableInfo::new(
+                "/usr/bin/tool",
+                extract_version_with("tool 3.11", &exact).unwrap(),
+            ),
+            ExecutableInfo::new(
+                "/opt/bin/tool",
+                extract_version_with("tool 3.11.2", &exact).unwrap(),
+            ),
+            ExecutableInfo::new(
+                "/usr/local/bin/tool",
+                extract_version_with("tool 3.11.0", &exact).unwrap(),
+            ),
+        ];
+        let partial = SelectionOptions {
+            partial_versions: true,
+            ..Default::default()
+        };
+
+        let latest = find_latest_version_with(info_list.clone(), &partial).unwrap();
+        assert_eq!(latest.path, "/opt/bin/tool");
+
+        // With 3.11.2 gone, an explicit 3.11.0 beats a bare 3.11, which the
+        // default comparison counts as a tie won by PATH order.
+        let info_list = vec![info_list[0].clone(), info_list[2].clone()];
+        assert_eq!(
+            find_latest_version_with(info_list.clone(), &partial)
+                .unwrap()
+                .path,
+            "/usr/local/bin/tool"
+        );
+        assert_eq!(
+            find_latest_with_conflicts(info_list.clone(), &partial)
+                .unwrap()
+                .1,
+            []
+        );
+        assert_eq!(
+            find_latest_version(info_list).unwrap().path,
+            "/usr/bin/tool"
+        );
+    }
+
+    #[test]
+    fn test_find_latest_with_conflicts() {
+        let info_list = vec![
+            ExecutableInfo::new("/opt/bin/python3", "3.10.0"),
+            ExecutableInfo::new("/usr/bin/python3", "3.11.0"),
+            ExecutableInfo::new("/usr/local/bin/python3", "3.11.0"),
+        ];
+
+        let (latest, conflicts) =
+            find_latest_with_conflicts(info_list.clone(), &SelectionOptions::default()).unwrap();
+        assert_eq!(latest.path, "/usr/bin/python3");
+        assert_eq!(
+            conflicts,
+            [ExecutableInfo::new("/usr/local/bin/python3", "3.11.0")]
+        );
+
+        let (_, conflicts) =
+            find_latest_with_conflicts(info_list[..2].to_vec(), &SelectionOptions::default())
+                .unwrap();
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_version_or_default_when_not_found() {
+        assert_eq!(
+            version_or("latest-version-no-such-command", "0.0.0"),
+            "0.0.0"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_version_or_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 4.2.1'");
+
+        assert_eq!(version_or(tool.to_str().unwrap(), "0.0.0"), "4.2.1");
+    }
+
+    #[test]
+    fn test_ignore_version_inside_url() {
+        let output = "See https://example.com/docs/3.2.1 for help\nmytool 1.4";
+        assert_eq!(extract_version(output), Some("3.2.1".to_string()));
+
+        let options = ExtractOptions {
+            ignore_urls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            extract_version_with(output, &options),
+            Some("1.4.0".to_string())
+        );
+
+        let only_url = "See https://example.com/docs/3.2 for help";
+        assert_eq!(
+            extract_version_with(only_url, &options),
+            Some("3.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_prerelease_and_build_metadata() {
+        for (output, expected) in [
+            ("tool 1.2.0-rc1", "1.2.0-rc1"),
+            ("tool 1.2.0-beta.2", "1.2.0-beta.2"),
+            ("tool 1.2.0+build.5", "1.2.0+build.5"),
+            ("tool 1.2.0-rc.1+build.5 (stable).", "1.2.0-rc.1+build.5"),
+        ] {
+            assert_eq!(extract_version(output).as_deref(), Some(expected));
+        }
+
+        let info_list = vec![
+            ExecutableInfo::new("/opt/rc/bin/tool", "1.2.0-rc1"),
+            ExecutableInfo::new("/usr/bin/tool", "1.2.0"),
+            ExecutableInfo::new("/opt/beta/bin/tool", "1.2.0-beta.2"),
+        ];
+        assert_eq!(
+            find_latest_version(info_list).unwrap().path,
+            "/usr/bin/tool"
+        );
+    }
+
+    #[test]
+    fn test_java_update_numbers() {
+        let older = extract_version("openjdk version \"1.8.0_251\"").unwrap();
+        let newer = extract_version("openjdk version \"1.8.0_302\"").unwrap();
+        assert_eq!(older, "1.8.0_251");
+        assert_eq!(newer, "1.8.0_302");
+
+        for (first, second) in [(&older, &newer), (&newer, &older)] {
+            let info_list = vec![
+                ExecutableInfo::new(format!("/opt/jdk{}/bin/java", first), first.as_str()),
+                ExecutableInfo::new(format!("/opt/jdk{}/bin/java", second), second.as_str()),
+            ];
+            assert_eq!(find_latest_version(info_list).unwrap().version, "1.8.0_302");
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_version_from_json_output() {
+        assert_eq!(extract_version(r#"{"version":"4.5.6"}"#).unwrap(), "4.5.6");
+        assert_eq!(
+            extract_version(
+                r#"{"go": "go1.22.0", "build": 20240101, "Version": "v4.5.6", "platform": "linux/amd64"}"#
+            )
+            .unwrap(),
+            "4.5.6"
+        );
+        assert_eq!(
+            extract_version("{\n  \"tag\": \"2.1\"\n}\n").unwrap(),
+            "2.1.0"
+        );
+        // Without a version key it's scraped like any other output.
+        assert_eq!(extract_version(r#"{"release": "3.0.1"}"#).unwrap(), "3.0.1");
+    }
+
+    #[test]
+    fn test_giant_numbers_are_not_versions() {
+        let giant = "123456789012345678901234567890";
+        for output in [
+            format!("tool build {}.7.1 release 2.3.1", giant),
+            format!("tool {} (2.3.1)", giant),
+            format!("tool 2.3.1+{}", giant),
+        ] {
+            assert_eq!(
+                extract_version(&output).as_deref(),
+                Some("2.3.1"),
+                "{}",
+                output
+            );
+            assert!(Version::parse(&extract_version(&output).unwrap()).is_ok());
+        }
+        assert_eq!(
+            extract_all_versions(&format!("{}.1.2 2.3.1", giant)),
+            ["2.3.1"]
+        );
+        assert_eq!(extract_version(&format!("build {}", giant)), None);
+
+        // 19 digits still fit in a semver component.
+        assert_eq!(
+            extract_version("tool 1234567890123456789.0.1").as_deref(),
+            Some("1234567890123456789.0.1")
+        );
+    }
+
+    #[test]
+    fn test_ansi_colors_are_ignored() {
+        let banner = "\x1b[1m\x1b[38;5;208mtool\x1b[0m version \x1b[32m2.4.1\x1b[0m";
+        assert_eq!(extract_version(banner).as_deref(), Some("2.4.1"));
+        assert_eq!(
+            extract_version_with(banner, &ExtractOptions::default()).as_deref(),
+            Some("2.4.1")
+        );
+        assert_eq!(extract_all_versions(banner), ["2.4.1"]);
+
+        let hyperlink = "\x1b]8;;https://example.com/3.0.0\x1b\\tool\x1b]8;;\x1b\\ 1.5";
+        assert_eq!(extract_version(hyperlink).as_deref(), Some("1.5.0"));
+    }
+
+    #[test]
+    fn test_bom_and_crlf_output() {
+        let output = "\u{feff}tool 1.2.3\r\nCopyright (c) 2019 Someone\r\n";
+        let extract = ExtractOptions {
+            regex: Some(r"(?m)^tool (?P<version>[\d.]+)$".to_string()),
+            ..Default::default()
+        };
+
+        for extract in [ExtractOptions::default(), extract] {
+            let info = probe_version("tool.exe", &["--version"], &extract, |_| {
+                Ok(output.to_string())
+            })
+            .unwrap();
+            assert_eq!(info.version, "1.2.3");
+        }
+    }
+
+    #[test]
+    fn test_vendor_prefixes_are_dropped() {
+        for (output, expected) in [
+            ("go version go1.21.4 linux/amd64", "1.21.4"),
+            ("go version go1.21 linux/amd64", "1.21.0"),
+            ("v18.16.0", "18.16.0"),
+            ("tool V2.1", "2.1.0"),
+            ("openjdk jdk17.0.2", "17.0.2"),
+        ] {
+            assert_eq!(extract_version(output).as_deref(), Some(expected));
+        }
+
+        // Only a prefix standing at the start of a word is dropped.
+        assert_eq!(extract_version("dev2 build").as_deref(), Some("2.0.0"));
+
+        let calver = ExtractOptions {
+            scheme: VersionScheme::CalVer,
+            ..Default::default()
+        };
+        assert_eq!(
+            extract_version_with("tool v2023.11.04", &calver).as_deref(),
+            Some("2023.11.4")
+        );
+    }
+
+    #[test]
+    fn test_calver_scheme() {
+        let options = ExtractOptions {
+            scheme: VersionScheme::CalVer,
+            ..Default::default()
+        };
+
+        for (output, expected) in [
+            ("tool snapshot 2023.11.04", "2023.11.4"),
+            ("tool nightly-20231104 (build 7)", "2023.11.4"),
+            ("tool 3.1 built 20240105", "2024.1.5"),
+        ] {
+            assert_eq!(
+                extract_version_with(output, &options).as_deref(),
+                Some(expected)
+            );
+        }
+        assert_eq!(extract_version_with("tool 20231399", &options), None);
+
+        let info_list = vec![
+            ExecutableInfo::new(
+                "/opt/nov/bin/tool",
+                extract_version_with("tool 20231104", &options).unwrap(),
+            ),
+            ExecutableInfo::new(
+                "/opt/jan/bin/tool",
+                extract_version_with("tool 2024.01.05", &options).unwrap(),
+            ),
+            ExecutableInfo::new(
+                "/opt/sep/bin/tool",
+                extract_version_with("tool 2023.09.30", &options).unwrap(),
+            ),
+        ];
+        assert_eq!(
+            find_latest_version(info_list).unwrap().path,
+            "/opt/jan/bin/tool"
+        );
+    }
+
+    #[test]
+    fn test_version_schemes() {
+        let extract = |output: &str, scheme: VersionScheme| {
+            let options = ExtractOptions {
+                scheme,
+                ..Default::default()
+            };
+            extract_version_with(output, &options)
+        };
+
+        for output in ["tool 3.11", "tool 2", "build 2023.11.04", "java 1.8.0_302"] {
+            assert_eq!(extract(output, VersionScheme::Semver), None, "{}", output);
+        }
+        assert_eq!(
+            extract("tool 3.11.4-rc1 (3.11)", VersionScheme::Semver).as_deref(),
+            Some("3.11.4-rc1")
+        );
+
+        let banner = "tool 2.3.4.5, protocol 1.0.0";
+        assert_eq!(
+            extract(banner, VersionScheme::Lenient).as_deref(),
+            Some("2.3.4.5")
+        );
+        assert_eq!(
+            extract(banner, VersionScheme::Auto).as_deref(),
+            Some("2.3.4")
+        );
+        assert_eq!(
+            extract(banner, VersionScheme::Auto),
+            extract_version(banner)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_semver_scheme_rejects_coerced_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 3.11'");
+        let tool = tool.to_str().unwrap();
+
+        let options = ProbeOptions {
+            extract: ExtractOptions {
+                scheme: VersionScheme::Semver,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            get_version_with(tool, &options),
+            Err(LatestVersionError::NoVersionInOutput { .. })
+        ));
+        assert_eq!(get_version(tool).unwrap().version, "3.11.0");
+    }
+
+    #[test]
+    fn test_extraction_shares_compiled_patterns_across_threads() {
+        let options = ExtractOptions {
+            regex: Some(r"build (?P<version>\d+\.\d+)".to_string()),
+            ..Default::default()
+        };
+        let anchored = ExtractOptions {
+            anchor: Some("release".to_string()),
+            ..Default::default()
+        };
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let (options, anchored) = (&options, &anchored);
+                    scope.spawn(move || {
+                        let output = format!("Tool 9.9.1 build 1.{} release 2.{}.0", i, i);
+                        (0..50)
+                            .map(|_| {
+                                (
+                                    extract_version_with(&output, options),
+                                    extract_version_with(&output, anchored),
+                                    extract_version(&output),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (i, results) in results.iter().enumerate() {
+            for result in results {
+                assert_eq!(
+                    result,
+                    &(
+                        Some(format!("1.{}", i)),
+                        Some(format!("2.{}.0", i)),
+                        Some("9.9.1".to_string())
+                    )
+                );
+            }
+        }
+
+        let pattern = options.regex.as_deref().unwrap();
+        assert!(std::sync::Arc::ptr_eq(
+            &cached_regex(pattern).unwrap(),
+            &cached_regex(pattern).unwrap()
+        ));
+        assert!(cached_regex("(unclosed").is_none());
+    }
+
+    #[test]
+    fn test_version_scheme_names() {
+        for (name, scheme) in [
+            ("semver", VersionScheme::Semver),
+            ("lenient", VersionScheme::Lenient),
+            ("calver", VersionScheme::CalVer),
+            ("auto", VersionScheme::Auto),
+        ] {
+            assert_eq!(name.parse::<VersionScheme>().unwrap(), scheme);
+            assert_eq!(scheme.to_string(), name);
+        }
+        assert_eq!(
+            "CalVer".parse::<VersionScheme>().unwrap(),
+            VersionScheme::CalVer
+        );
+
+        let error = "semantic".parse::<VersionScheme>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"unknown version scheme "semantic"; expected one of semver, lenient, calver or auto"#
+        );
+    }
+
+    #[test]
+    fn test_custom_regex_extraction() {
+        let banner = "Tool v2024w05 (build 0x1f3a)";
+        let week = regex::Regex::new(r"v(?P<version>\d{4}w\d{2})").unwrap();
+        assert_eq!(
+            extract_version_with_regex(banner, &week),
+            Some("2024w05".to_string())
+        );
+
+        let build = regex::Regex::new(r"0x[0-9a-f]+").unwrap();
+        assert_eq!(
+            extract_version_with_regex(banner, &build),
+            Some("0x1f3a".to_string())
+        );
+
+        let options = ExtractOptions {
+            regex: Some(r"v(?P<version>\d{4}w\d{2})".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            extract_version_with(banner, &options),
+            Some("2024w05".to_string())
+        );
+        assert_eq!(extract_version_with("Tool 1.2.3", &options), None);
+        assert_eq!(extract_version(banner), Some("2024.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_repeated_extraction_reuses_patterns() {
+        let outputs = [
+            ("Python 3.11.4", "3.11.4"),
+            ("tool 1.2-3", "1.2.3"),
+            ("Node v18.16", "18.16.0"),
+            ("Git version 2", "2.0.0"),
+        ];
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..50 {
+                        for (output, expected) in outputs {
+                            assert_eq!(extract_version(output).as_deref(), Some(expected));
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_anchored_and_nth_extraction() {
+        let options = |anchor: Option<&str>, nth: Option<usize>| ExtractOptions {
+            anchor: anchor.map(str::to_string),
+            nth,
+            ..Default::default()
+        };
+        let banner = "Foo 2.0.1 (build 12345) release 3.4.1-rc2";
+
+        assert_eq!(extract_version(banner).as_deref(), Some("2.0.1"));
+        assert_eq!(
+            extract_version_with(banner, &options(Some("release"), None)).as_deref(),
+            Some("3.4.1-rc2")
+        );
+        assert_eq!(
+            extract_version_with(banner, &options(None, Some(2))).as_deref(),
+            Some("12345.0.0")
+        );
+        assert_eq!(
+            extract_version_with(banner, &options(Some("build"), Some(2))).as_deref(),
+            Some("3.4.1-rc2")
+        );
+        assert_eq!(
+            extract_version_with(
+                "Copyright 2024 Acme 1.0 Inc. VERSION 1.7.2",
+                &options(Some("version"), None)
+            )
+            .as_deref(),
+            Some("1.7.2")
+        );
+        assert_eq!(
+            extract_version_with(banner, &options(Some("version"), None)),
+            None
+        );
+        assert_eq!(extract_version_with(banner, &options(None, Some(9))), None);
+    }
+
+    #[test]
+    fn test_normalize_policies() {
+        let cases = [
+            (NormalizePolicy::PadToPatch, ["2.0.0", "3.11.0", "3.11.4"]),
+            (NormalizePolicy::Exact, ["2", "3.11", "3.11.4"]),
+            (NormalizePolicy::MajorMinor, ["2.0", "3.11", "3.11"]),
+        ];
+
+        for (policy, expected) in cases {
+            let options = ExtractOptions {
+                normalize: policy,
+                ..Default::default()
+            };
+            for (input, expected) in ["tool 2", "tool 3.11", "tool 3.11.4"].iter().zip(expected) {
+                assert_eq!(
+                    extract_version_with(input, &options).as_deref(),
+                    Some(expected),
+                    "{:?} on {:?}",
+                    policy,
+                    input
+                );
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compare_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = write_script(dir.path(), "old-tool", "echo 'tool 1.9.2'");
+        let new = write_script(dir.path(), "new-tool", "echo 'tool 1.10.0'");
+
+        let (old_info, new_info, ordering) =
+            compare_paths(old.to_str().unwrap(), new.to_str().unwrap()).unwrap();
+
+        assert_eq!(old_info.version, "1.9.2");
+        assert_eq!(new_info.version, "1.10.0");
+        assert_eq!(ordering, Ordering::Less);
+
+        // Both are probed according to the options.
+        let picky = write_script(
+            dir.path(),
+            "picky-tool",
+            "if [ \"$1\" = -V ]; then echo 'tool 2.0.0'; else echo 'tool 0.1.0'; fi",
+        );
+        let options = ProbeOptions {
+            flags: vec!["-V".to_string()],
+            ..Default::default()
+        };
+        let (picky_info, _, ordering) =
+            compare_paths_with(picky.to_str().unwrap(), new.to_str().unwrap(), &options).unwrap();
+        assert_eq!(picky_info.version, "2.0.0");
+        assert_eq!(ordering, Ordering::Greater);
+    }
+
+    #[test]
+    fn test_exclude_prereleases() {
+        let info_list = vec![
+            ExecutableInfo::new("/usr/bin/tool", "1.9.0"),
+            ExecutableInfo::new("/opt/tool/bin/tool", "2.0.0-rc1"),
+            ExecutableInfo::new("/usr/local/bin/tool", "1.8.5"),
+        ];
+
+        let latest = find_latest_version(info_list.clone()).unwrap();
+        assert_eq!(latest.version, "2.0.0-rc1");
+
+        let options = SelectionOptions {
+            exclude_prereleases: true,
+            ..Default::default()
+        };
+        let stable = find_latest_version_with(info_list.clone(), &options).unwrap();
+        assert_eq!(stable.path, "/usr/bin/tool");
+        assert_eq!(stable.version, "1.9.0");
+
+        let prereleases: Vec<bool> = info_list
+            .iter()
+            .map(ExecutableInfo::is_prerelease)
+            .collect();
+        assert_eq!(prereleases, [false, true, false]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_iter_executables_scans_lazily() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let last_dir = tempfile::tempdir().unwrap();
+        let links = tempfile::tempdir().unwrap();
+        let first = write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+        let last = write_script(last_dir.path(), "tool", "echo 'tool 2.0.0'");
+        let alias = links.path().join("bin");
+        std::os::unix::fs::symlink(first_dir.path(), &alias).unwrap();
+
+        let scanned = std::cell::Cell::new(0);
+        let dirs = [first_dir.path(), alias.as_path(), last_dir.path()]
+            .into_iter()
+            .map(PathBuf::from)
+            .inspect(|_| scanned.set(scanned.get() + 1));
+        let mut executables = iter_executables_in("tool", dirs);
+        assert_eq!(scanned.get(), 0);
+
+        assert_eq!(executables.next().unwrap(), first.to_str().unwrap());
+        assert_eq!(scanned.get(), 1);
+
+        // The alias of the first directory is still skipped as a duplicate.
+        assert_eq!(executables.next().unwrap(), last.to_str().unwrap());
+        assert_eq!(scanned.get(), 3);
+        assert!(executables.next().is_none());
+
+        assert_eq!(
+            iter_executables_in("missing", vec![first_dir.path().to_path_buf()]).count(),
+            0
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dangling_symlink_and_empty_file_are_skipped() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let broken_dir = tempfile::tempdir().unwrap();
+        let good_dir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(
+            broken_dir.path().join("missing-target"),
+            broken_dir.path().join("tool"),
+        )
+        .unwrap();
+        let tool = write_script(good_dir.path(), "tool", "echo 'tool 1.0.0'");
+
+        let empty = broken_dir.path().join("empty");
+        std::fs::write(&empty, "").unwrap();
+        std::fs::set_permissions(&empty, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(matches!(
+            find_executables_in("empty", &[broken_dir.path().to_path_buf()]),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+
+        let broken_only = vec![broken_dir.path().to_path_buf()];
+        assert!(matches!(
+            find_executables_in("tool", &broken_only),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+
+        let both = vec![
+            broken_dir.path().to_path_buf(),
+            good_dir.path().to_path_buf(),
+        ];
+        assert_eq!(
+            find_executables_in("tool", &both).unwrap(),
+            [tool.to_str().unwrap()]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_executables_with_foreign_separator() {
+        let dir1 = tempfile::tempdir().unwrap();
+        let dir2 = tempfile::tempdir().unwrap();
+        let tool1 = write_script(dir1.path(), "tool", "echo 'tool 1.0.0'");
+        let tool2 = write_script(dir2.path(), "tool", "echo 'tool 2.0.0'");
+
+        let path = format!("{};;{}", dir1.path().display(), dir2.path().display());
+        let found = find_executables_in_with_sep("tool", &path, ';').unwrap();
+
+        assert_eq!(found, [tool1.to_str().unwrap(), tool2.to_str().unwrap()]);
+    }
+
+    #[test]
+    fn test_extract_all_versions_client_and_server() {
+        let output = "Client: Docker Engine - Community\n \
+                      Version:           24.0.7\n \
+                      API version:       1.43\n\n\
+                      Server: Docker Engine - Community\n \
+                      Engine:\n  \
+                      Version:          24.0.5\n  \
+                      API version:      1.43 (minimum version 1.12)\n \
+                      containerd:\n  \
+                      Version:          1.6.24\n";
+
+        assert_eq!(extract_all_versions(output), ["24.0.7", "24.0.5", "1.6.24"]);
+        assert!(extract_all_versions("no numbers here").is_empty());
+    }
+
+    #[test]
+    fn test_sort_executables() {
+        let mut info_list = vec![
+            ExecutableInfo::new("/usr/local/bin/tool", "1.2.0"),
+            ExecutableInfo::new("/opt/bin/tool", "1.10.0"),
+            ExecutableInfo::new("/usr/bin/tool", "1.9.0"),
+        ];
+
+        sort_executables(&mut info_list, SortOrder::Path);
+        let paths: Vec<&str> = info_list.iter().map(|info| info.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["/opt/bin/tool", "/usr/bin/tool", "/usr/local/bin/tool"]
+        );
+
+        sort_executables(&mut info_list, SortOrder::Version);
+        let versions: Vec<&str> = info_list.iter().map(|info| info.version.as_str()).collect();
+        assert_eq!(versions, ["1.10.0", "1.9.0", "1.2.0"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_executables_in_explicit_dirs() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let second_dir = tempfile::tempdir().unwrap();
+        let first = write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+        let second = write_script(second_dir.path(), "tool", "echo 'tool 2.0.0'");
+
+        let search_dirs = [
+            second_dir.path().to_path_buf(),
+            first_dir.path().to_path_buf(),
+        ];
+        assert_eq!(
+            find_executables_in("tool", &search_dirs).unwrap(),
+            [second.to_str().unwrap(), first.to_str().unwrap()]
+        );
+        assert!(matches!(
+            find_executables_in("other", &search_dirs),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_every_path_entry_is_searched() {
+        let dirs: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        let tools: Vec<PathBuf> = dirs
+            .iter()
+            .map(|dir| write_script(dir.path(), "tool", "echo 'tool 1.0.0'"))
+            .collect();
+        let path = std::env::join_paths(dirs.iter().map(|dir| dir.path())).unwrap();
+
+        let expected: Vec<&str> = tools.iter().map(|tool| tool.to_str().unwrap()).collect();
+        assert_eq!(
+            find_executables_in("tool", &path_search_dirs(&path)).unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_duplicates_are_probed_once() {
+        let real_dir = tempfile::tempdir().unwrap();
+        let link_dir = tempfile::tempdir().unwrap();
+        let tool = write_script(real_dir.path(), "tool", "echo 'tool 1.0.0'");
+        std::os::unix::fs::symlink(&tool, link_dir.path().join("tool")).unwrap();
+
+        let path =
+            std::env::join_paths([real_dir.path(), link_dir.path(), real_dir.path()]).unwrap();
+        assert_eq!(
+            find_executables_in("tool", &path_search_dirs(&path)).unwrap(),
+            [tool.to_str().unwrap()]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_path_entries_are_skipped() {
+        let local_dir = tempfile::Builder::new().tempdir_in(".").unwrap();
+        write_script(local_dir.path(), "tool", "echo 'tool 1.0.0'");
+        let relative = local_dir.path().file_name().unwrap().to_str().unwrap();
+
+        for path in [
+            ".".to_string(),
+            relative.to_string(),
+            format!("./{}", relative),
+        ] {
+            assert!(matches!(
+                find_executables_in_with_sep("tool", &path, ':'),
+                Err(LatestVersionError::CommandNotFound(_))
+            ));
+        }
+
+        let absolute = std::fs::canonicalize(local_dir.path()).unwrap();
+        let path = format!(".:{}", absolute.display());
+        assert_eq!(
+            find_executables_in_with_sep("tool", &path, ':').unwrap(),
+            [absolute.join("tool").to_str().unwrap()]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_silent_failure_suggests_a_terminal() {
+        let dir = tempfile::tempdir().unwrap();
+        let silent = write_script(dir.path(), "silent", "exit 1");
+        let usage = write_script(
+            dir.path(),
+            "usage",
+            "echo 'usage: usage [opts]' >&2; exit 2",
+        );
+
+        let error = get_version(silent.to_str().unwrap()).unwrap_err();
+        assert!(matches!(
+            &error,
+            LatestVersionError::SilentFailure { path, .. } if path == silent.to_str().unwrap()
+        ));
+        assert!(error.to_string().contains("terminal"), "{}", error);
+
+        // Printing anything at all is a plain extraction failure.
+        assert!(matches!(
+            get_version(usage.to_str().unwrap()),
+            Err(LatestVersionError::NoVersionInOutput { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_arg0_selects_a_multicall_applet() {
+        // A shell reading its script from stdin sees its own argv[0] as `$0`,
+        // just as a multicall binary picks the applet from it.
+        let options = ProbeOptions {
+            probes: vec![vec!["-s".to_string()]],
+            stdin_input: Some(
+                "case \"$0\" in wget) echo 'GNU Wget 1.21.4' ;; *) echo 'BusyBox v1.36.1' ;; esac"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_version_with("/bin/sh", &options).unwrap().version,
+            "1.36.1"
+        );
+
+        let options = ProbeOptions {
+            arg0: Some("wget".to_string()),
+            ..options
+        };
+        let info = get_version_with("/bin/sh", &options).unwrap();
+        assert_eq!(info.version, "1.21.4");
+        assert_eq!(info.path, "/bin/sh");
+
+        // The built-in flags follow the applet's name rather than the binary's.
+        let options = ProbeOptions {
+            arg0: Some("java".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(probes_for("/bin/busybox", &options)[0], ["-version"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_is_installed() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 3.11.4'");
+        let tool = tool.to_str().unwrap();
+        let requirement = |req: &str| semver::VersionReq::parse(req).unwrap();
+
+        assert!(is_installed(tool, &requirement("~3.11")).unwrap());
+        assert!(!is_installed(tool, &requirement(">=3.12")).unwrap());
+        assert!(matches!(
+            is_installed(
+                dir.path().join("missing").to_str().unwrap(),
+                &requirement("~3.11")
+            ),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stdout_and_stderr_are_searched_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        // Joined together, these would read as `build 123.4.1`.
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            "printf 'tool build 12'; printf '3.4.1' >&2",
+        );
+        let info = get_version(tool.to_str().unwrap()).unwrap();
+        assert_eq!(info.version, "12.0.0");
+
+        let stderr_only = write_script(dir.path(), "quiet", "printf 'quiet 3.4.1' >&2");
+        let info = get_version(stderr_only.to_str().unwrap()).unwrap();
+        assert_eq!(info.version, "3.4.1");
+
+        // Only the two together hold a version, and then they are joined.
+        let extract = ExtractOptions {
+            regex: Some(r"tool (?P<version>\d+\.\d+) ok".to_string()),
+            ..Default::default()
+        };
+        let split = write_script(dir.path(), "split", "printf 'tool 2.5'; printf ' ok' >&2");
+        let options = ProbeOptions {
+            extract,
+            ..Default::default()
+        };
+        let info = get_version_with(split.to_str().unwrap(), &options).unwrap();
+        assert_eq!(info.version, "2.5");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_multi_argument_probes() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            "if [ \"$1 $2\" = 'version --short' ]; then echo '7.2.1'; else echo 'usage: tool'; fi",
+        );
+        let tool = tool.to_str().unwrap();
+
+        assert!(matches!(
+            get_version(tool),
+            Err(LatestVersionError::NoVersionInOutput { .. })
+        ));
+
+        let options = ProbeOptions {
+            probes: vec![
+                vec!["--version".to_string(), "--verbose".to_string()],
+                vec!["version".to_string(), "--short".to_string()],
+            ],
+            ..Default::default()
+        };
+        let info = get_version_with(tool, &options).unwrap();
+        assert_eq!(info.version, "7.2.1");
+        assert_eq!(info.probe_command, [tool, "version", "--short"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_version_after_leading_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(
+            dir.path(),
+            "wrapper",
+            "if [ \"$1 $2\" = 'run tool' ]; then echo \"tool 4.5.6 ($3)\"; else echo 'wrapper 1.0.0'; fi",
+        );
+        let tool = tool.to_str().unwrap();
+
+        let info = get_version_args(tool, &["run", "tool"], "--version").unwrap();
+        assert_eq!(info.version, "4.5.6");
+        assert_eq!(info.probe_command, [tool, "run", "tool", "--version"]);
+        assert_eq!(get_version(tool).unwrap().version, "1.0.0");
+
+        let options = ProbeOptions {
+            args: vec!["run".to_string(), "tool".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            get_version_with(tool, &options).unwrap().probe_command[3],
+            "--version"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_command_is_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 1.2.3'");
+        let picky = write_script(
+            dir.path(),
+            "picky",
+            "if [ \"$1\" = \"-V\" ]; then echo 'picky 0.4.1'; fi",
+        );
+        let tool = tool.to_str().unwrap();
+        let picky = picky.to_str().unwrap();
+
+        assert_eq!(
+            get_version(tool).unwrap().probe_command,
+            [tool, "--version"]
+        );
+        assert_eq!(get_version(picky).unwrap().probe_command, [picky, "-V"]);
+    }
+
+    #[test]
+    fn test_min_fuzzy_components() {
+        let info_list = vec![
+            ExecutableInfo::new("/usr/bin/tool", "1.4"),
+            ExecutableInfo::new("/opt/bin/tool", "7"),
+        ];
+
+        let latest = find_latest_version(info_list.clone()).unwrap();
+        assert_eq!(latest.path, "/opt/bin/tool");
+
+        let options = SelectionOptions {
+            min_fuzzy_components: 2,
+            ..Default::default()
+        };
+        let latest = find_latest_version_with(info_list, &options).unwrap();
+        assert_eq!(latest.path, "/usr/bin/tool");
+
+        let single = vec![ExecutableInfo::new("/opt/bin/tool", "7")];
+        assert!(find_latest_version_with(single, &options).is_err());
+    }
+
+    #[test]
+    fn test_builtin_version_flags() {
+        let known = builtin_version_flags();
+        let flags_for = |name: &str| known.iter().find(|(command, _)| *command == name);
+
+        assert_eq!(flags_for("java"), Some(&("java", &["-version"][..])));
+        assert_eq!(flags_for("go"), Some(&("go", &["version"][..])));
+        assert!(flags_for("python3").is_none());
+
+        assert_eq!(
+            version_flags_for("/usr/bin/java"),
+            ["-version", "--version", "-v", "-V", "version"]
+        );
+        assert_eq!(
+            version_flags_for("/usr/bin/python3"),
+            ["--version", "-v", "-V", "version"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_builtin_flags_are_tried_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let java = write_script(
+            dir.path(),
+            "java",
+            "if [ \"$1\" = \"-version\" ]; then echo 'openjdk version \"17.0.9\"' >&2; fi",
+        );
+        let java = java.to_str().unwrap();
+
+        let info = get_version(java).unwrap();
+        assert_eq!(info.version, "17.0.9");
+        assert_eq!(info.probe_command, [java, "-version"]);
+    }
+
+    #[test]
+    fn test_dash_separated_patch() {
+        assert_eq!(extract_version("tool 1.2-3"), Some("1.2.3".to_string()));
+        assert_eq!(extract_version("tool 1.2-10"), Some("1.2.10".to_string()));
+        assert_eq!(extract_version("tool 1.2.0-3"), Some("1.2.0-3".to_string()));
+
+        let info_list = vec![
+            ExecutableInfo::new("/usr/bin/tool", extract_version("tool 1.2-10").unwrap()),
+            ExecutableInfo::new("/opt/bin/tool", extract_version("tool 1.2-3").unwrap()),
+        ];
+        assert_eq!(
+            find_latest_version(info_list).unwrap().path,
+            "/usr/bin/tool"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_custom_version_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            r#"[ "$1" = "--print-version" ] && echo 'tool 2.5.0'"#,
+        );
+        let tool = tool.to_str().unwrap();
+
+        let info = get_version_with_flag(tool, &["--ver", "--print-version"]).unwrap();
+        assert_eq!(info.version, "2.5.0");
+        assert_eq!(info.probe_command, [tool, "--print-version"]);
+
+        assert!(get_version(tool).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hanging_probe_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let hang = write_script(dir.path(), "hang", "sleep 30");
+        let reads_stdin = write_script(dir.path(), "repl", "cat\necho 'repl 1.0.0'");
+
+        let started = Instant::now();
+        assert!(matches!(
+            get_version_with_timeout(hang.to_str().unwrap(), Duration::from_millis(200)),
+            Err(LatestVersionError::Timeout(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let info = get_version_with_timeout(reads_stdin.to_str().unwrap(), Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(info.version, "1.0.0");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_with_stdin_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let filter = write_script(
+            dir.path(),
+            "filter",
+            "read line\nif [ \"$line\" = \"version\" ]; then echo 'filter 3.1.4'; fi",
+        );
+
+        let options = ProbeOptions {
+            stdin_input: Some("version\n".to_string()),
+            ..Default::default()
+        };
+        let info = get_version_with(filter.to_str().unwrap(), &options).unwrap();
+        assert_eq!(info.version, "3.1.4");
+    }
+
+    #[test]
+    fn test_version_buried_after_wrapper_noise() {
+        let noise: String = (0..50)
+            .map(|_| "wrapper: setting up environment, please wait\n")
+            .collect();
+        let output = format!("{}tool version 4.5.6\n", noise);
+
+        assert_eq!(extract_version(&output), Some("4.5.6".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_version_from_exec_wrapper_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = write_script(dir.path(), "real-tool", "echo 'tool version 4.5.6'");
+        let wrapper = write_script(
+            dir.path(),
+            "tool",
+            &format!(
+                "i=0\nwhile [ $i -lt 50 ]; do echo 'wrapper: loading'; i=$((i+1)); done\nexec '{}' \"$@\"",
+                real.display()
+            ),
+        );
+
+        let info = get_version(wrapper.to_str().unwrap()).unwrap();
+        assert_eq!(info.version, "4.5.6");
+    }
+
+    #[test]
+    fn test_missing_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("not-a-dir");
+        std::fs::write(&file, "").unwrap();
+        let missing = dir.path().join("does-not-exist");
+
+        let path = format!(
+            "{}::{}:{}",
+            dir.path().display(),
+            missing.display(),
+            file.display()
+        );
+
+        assert_eq!(missing_dirs_in_with_sep(&path, ':'), [missing, file]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_latest_command_semver() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 3.11.4'");
+        let snapshot = write_script(dir.path(), "snapshot", "echo 'snapshot 2024.01.05'");
+        let tool = tool.to_str().unwrap();
+
+        let (path, version) = find_latest_command_semver(tool).unwrap();
+        assert_eq!(path, tool);
+        assert_eq!(version, Version::new(3, 11, 4));
+
+        assert!(matches!(
+            find_latest_command_semver(snapshot.to_str().unwrap()),
+            Err(LatestVersionError::VersionParsingError(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_missing_version_error_shows_the_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            "echo 'unknown option'\necho 'try --help'",
+        );
+        let chatty = write_script(
+            dir.path(),
+            "chatty",
+            "i=0\nwhile [ $i -lt 500 ]; do printf x; i=$((i + 1)); done",
+        );
+
+        let error = get_version(tool.to_str().unwrap()).unwrap_err();
+        assert!(matches!(
+            &error,
+            LatestVersionError::NoVersionInOutput { path, .. } if path == tool.to_str().unwrap()
+        ));
+        let message = error.to_string();
+        assert!(message.contains(tool.to_str().unwrap()), "{}", message);
+        assert!(
+            message.contains(r#""unknown option\ntry --help""#),
+            "{}",
+            message
+        );
+
+        let message = get_version(chatty.to_str().unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.ends_with(&format!("{:?}...", "x".repeat(200))),
+            "{}",
+            message
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_version_on_stderr_with_failing_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 1.2.3' >&2\nexit 1");
+        let noisy = write_script(
+            dir.path(),
+            "noisy",
+            "echo 'usage: noisy [options]'\necho 'noisy version 4.5.6' >&2\nexit 2",
+        );
+
+        let info = get_version(tool.to_str().unwrap()).unwrap();
+        assert_eq!(info.version, "1.2.3");
+        assert_eq!(info.probe_command[1], "--version");
+
+        assert_eq!(
+            get_version(noisy.to_str().unwrap()).unwrap().version,
+            "4.5.6"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_version_verbose_returns_raw_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let banner = "Tool v2.0.1 (built 2024-01-05)\n  copyright 1999-2024\n";
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            &format!("printf '%s' '{}'\necho 'warning: on stderr' >&2", banner),
+        );
+
+        let (info, raw) = get_version_verbose(tool.to_str().unwrap()).unwrap();
+        assert_eq!(info.version, "2.0.1");
+        assert_eq!(raw, format!("{}warning: on stderr\n", banner));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_first_never_runs_the_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let tool = write_script(
+            dir.path(),
+            "tool",
+            &format!("echo run >> '{}'\necho 'tool 1.0.0'", log.display()),
+        );
+        let tool = tool.to_str().unwrap();
+
+        assert_eq!(find_first(tool).unwrap(), tool);
+        assert!(!log.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_latest_commands_keeps_going_past_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 1.4.2'");
+        let silent = write_script(dir.path(), "silent", "echo 'no version here'");
+        let missing = dir.path().join("missing");
+        let commands = [
+            silent.to_str().unwrap(),
+            tool.to_str().unwrap(),
+            missing.to_str().unwrap(),
+        ];
+
+        let results = find_latest_commands(&commands);
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|(command, _)| command)
+                .collect::<Vec<_>>(),
+            commands
+        );
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].1.as_ref().unwrap().version, "1.4.2");
+        assert!(matches!(
+            results[2].1,
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_excluded_dirs_match_symlinked_aliases() {
+        let keep_dir = tempfile::tempdir().unwrap();
+        let wrapper_dir = tempfile::tempdir().unwrap();
+        let other_dir = tempfile::tempdir().unwrap();
+        let kept = write_script(keep_dir.path(), "tool", "echo 'tool 1.0.0'");
+        write_script(wrapper_dir.path(), "tool", "echo 'tool 99.0.0'");
+        let other = write_script(other_dir.path(), "tool", "echo 'tool 2.0.0'");
+        let alias = keep_dir.path().join("wrappers");
+        std::os::unix::fs::symlink(wrapper_dir.path(), &alias).unwrap();
+
+        // The wrappers are on PATH only through the alias; excluding the real
+        // directory still drops them.
+        let search_dirs = vec![
+            keep_dir.path().to_path_buf(),
+            alias.clone(),
+            other_dir.path().to_path_buf(),
+        ];
+        let search_dirs = without_dirs(search_dirs, &[wrapper_dir.path().to_path_buf()]);
+
+        assert_eq!(
+            find_executables_in("tool", &search_dirs).unwrap(),
+            [kept.to_str().unwrap(), other.to_str().unwrap()]
+        );
+        assert!(in_dirs(
+            &alias.join("tool"),
+            &[wrapper_dir.path().to_path_buf()]
+        ));
+        assert!(!in_dirs(&kept, &[wrapper_dir.path().to_path_buf()]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_candidates_newer_than_first() {
+        let first_dir = tempfile::tempdir().unwrap();
+        let same_dir = tempfile::tempdir().unwrap();
+        let newer_dir = tempfile::tempdir().unwrap();
+        write_script(first_dir.path(), "tool", "echo 'tool 1.2.0'");
+        write_script(same_dir.path(), "tool", "echo 'tool 1.2.0'");
+        let newer = write_script(newer_dir.path(), "tool", "echo 'tool 1.10.0'");
+
+        let search_dirs = vec![
+            first_dir.path().to_path_buf(),
+            same_dir.path().to_path_buf(),
+            newer_dir.path().to_path_buf(),
+        ];
+        let executables = find_executables_in("tool", &search_dirs).unwrap();
+        let (info_list, _) = probe_executables(executables, get_version);
+
+        let candidates = newer_than_first(info_list.clone());
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].path, newer.to_str().unwrap());
+        assert_eq!(candidates[0].version, "1.10.0");
+
+        assert!(newer_than_first(info_list.into_iter().rev().collect()).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_candidates_need_an_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tool = write_script(dir.path(), "tool", "echo 'tool 1.0.0'");
+        assert!(is_executable_file(&tool));
+
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(!is_executable_file(&tool));
+        assert!(!is_executable_file(dir.path()));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_candidates_follow_pathext_order() {
+        assert_eq!(
+            candidate_names_with("tool", ".COM;.EXE;.BAT;.CMD"),
+            ["tool", "tool.COM", "tool.EXE", "tool.BAT", "tool.CMD"]
+        );
+        assert_eq!(
+            candidate_names_with("tool", ".BAT;;.EXE"),
+            ["tool", "tool.BAT", "tool.EXE"]
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_exe_wins_over_bat() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tool.bat"), "@echo tool 1.0.0").unwrap();
+        std::fs::write(dir.path().join("tool.exe"), "").unwrap();
+        std::fs::write(dir.path().join("other.bat"), "@echo other 1.0.0").unwrap();
+
+        let search_dirs = vec![dir.path().to_path_buf()];
+        let found = find_executables_in("tool", &search_dirs).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(
+            found[0].to_ascii_lowercase().ends_with("tool.exe"),
+            "{:?}",
+            found
+        );
+
+        let found = find_executables_in("other", &search_dirs).unwrap();
+        assert!(
+            found[0].to_ascii_lowercase().ends_with("other.bat"),
+            "{:?}",
+            found
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_candidates_match_pathext() {
+        use std::path::Path;
+
+        let pathext = ".COM;.EXE;.BAT;.CMD";
+        for name in ["tool.exe", "tool.EXE", "tool.Bat", "tool.cmd", "tool.com"] {
+            assert!(has_pathext_extension(Path::new(name), pathext), "{}", name);
+        }
+        for name in ["tool", "tool.txt", "tool.exe.bak"] {
+            assert!(!has_pathext_extension(Path::new(name), pathext), "{}", name);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_display_path_drops_pathext() {
+        let pathext = ".COM;.EXE;.BAT;.CMD";
+        assert_eq!(strip_pathext(r"C:\bin\tool.exe", pathext), r"C:\bin\tool");
+        assert_eq!(strip_pathext(r"C:\bin\tool.Cmd", pathext), r"C:\bin\tool");
+        assert_eq!(
+            strip_pathext(r"C:\bin\tool.ps1", pathext),
+            r"C:\bin\tool.ps1"
+        );
+        assert_eq!(strip_pathext(r"C:\bin.d\tool", pathext), r"C:\bin.d\tool");
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tool.bat"), "@echo tool 1.0.0").unwrap();
+        std::fs::write(dir.path().join("tool.exe"), "").unwrap();
+
+        let search_dirs = vec![dir.path().to_path_buf()];
+        let found = find_executables_in("tool", &search_dirs).unwrap();
+        let info = ExecutableInfo::new(found[0].clone(), "1.0.0");
+        assert_eq!(
+            info.display_path(),
+            dir.path().join("tool").to_str().unwrap()
+        );
+        assert!(info.path.to_ascii_lowercase().ends_with("tool.exe"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parallel_probe_keeps_path_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let versions = [
+            "1.0.0", "2.3.0", "1.9.9", "2.3.0", "0.4.1", "2.3.0", "2.2.10",
+        ];
+        let executables: Vec<String> = versions
+            .iter()
+            .enumerate()
+            .map(|(index, version)| {
+                let name = format!("tool{}", index);
+                let script =
+                    write_script(dir.path(), &name, &format!("echo '{} {}'", name, version));
+                script.to_str().unwrap().to_string()
+            })
+            .collect();
+
+        for _ in 0..5 {
+            let (info_list, failures) = probe_executables(executables.clone(), get_version);
+            assert!(failures.is_empty());
+
+            let probed: Vec<&str> = info_list.iter().map(|info| info.path.as_str()).collect();
+            assert_eq!(probed, executables);
+
+            let latest = find_latest_version(info_list).unwrap();
+            assert_eq!(latest.path, executables[1]);
+            assert_eq!(latest.version, "2.3.0");
+        }
+    }
+
+    #[test]
+    fn test_select_matching() {
+        let info_list = vec![
+            ExecutableInfo::new("/usr/bin/python3", "3.8.10"),
+            ExecutableInfo::new("/usr/local/bin/python3", "3.12.1"),
+            ExecutableInfo::new("/opt/python/bin/python3", "3.10.4"),
+            ExecutableInfo::new("/usr/bin/python", "2.7.18"),
+            ExecutableInfo::new("/opt/snapshot/bin/python3", "3.13"),
+        ];
+        let paths = |requirement: &str| -> Vec<String> {
+            let requirement = semver::VersionReq::parse(requirement).unwrap();
+            select_matching(info_list.clone(), &requirement)
+                .into_iter()
+                .map(|info| info.path)
+                .collect()
+        };
+
+        assert_eq!(
+            paths(">=3.10"),
+            ["/usr/local/bin/python3", "/opt/python/bin/python3"]
+        );
+        assert_eq!(
+            paths("^3"),
+            [
+                "/usr/local/bin/python3",
+                "/opt/python/bin/python3",
+                "/usr/bin/python3"
+            ]
+        );
+        assert!(paths(">=4").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_report() {
+        let executable_dir = tempfile::tempdir().unwrap();
+        let plain_dir = tempfile::tempdir().unwrap();
+        let empty_dir = tempfile::tempdir().unwrap();
+        let missing_dir = empty_dir.path().join("missing");
+
+        let tool = write_script(executable_dir.path(), "tool", "echo 'tool 1.0.0'");
+        let plain = plain_dir.path().join("tool");
+        std::fs::write(&plain, "not a program").unwrap();
+
+        let search_dirs = vec![
+            executable_dir.path().to_path_buf(),
+            plain_dir.path().to_path_buf(),
+            empty_dir.path().to_path_buf(),
+            missing_dir.clone(),
+        ];
+        let report = scan_dirs("tool", &search_dirs);
+
+        assert_eq!(
+            report.dirs,
+            [
+                DirScan {
+                    dir: search_dirs[0].clone(),
+                    exists: true,
+                    matches: vec![FileMatch {
+                        path: tool,
+                        executable: true
+                    }],
+                },
+                DirScan {
+                    dir: search_dirs[1].clone(),
+                    exists: true,
+                    matches: vec![FileMatch {
+                        path: plain,
+                        executable: false
+                    }],
+                },
+                DirScan {
+                    dir: search_dirs[2].clone(),
+                    exists: true,
+                    matches: vec![],
+                },
+                DirScan {
+                    dir: missing_dir,
+                    exists: false,
+                    matches: vec![],
+                },
+            ]
+        );
+        assert!(report.to_string().contains("does not exist"));
     }
 }
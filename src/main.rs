@@ -1,234 +1,988 @@
-use clap::Parser;
-use semver::Version;
-#[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
-use std::process::{Command, Output};
-use thiserror::Error;
-use version_compare::Cmp;
-use which::which;
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{ArgAction, ArgGroup, Parser, ValueEnum};
+use latest_version::{
+    builtin_version_flags, compare_versions, find_all_versions_streamed,
+    find_all_versions_verbose_with, find_all_versions_with, find_executables_with,
+    find_latest_command_cached, find_latest_command_probed, find_latest_command_verbose_with,
+    find_latest_version_with, get_version_with, is_installed_with, missing_path_dirs, plan_probes,
+    probe_all_with, resolve_active_with, scan_path, select_matching, sort_executables,
+    ExecutableInfo, LatestVersionError, NormalizePolicy, ProbeOptions, SelectionOptions, SortOrder,
+    VersionCache, VersionScheme,
+};
+use semver::VersionReq;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Error, Debug)]
-enum LatestVersionError {
-    #[error("Command not found: {0}")]
-    CommandNotFound(String),
+#[derive(Parser, Debug)]
+#[command(
+    group = ArgGroup::new("verdict").args(["newer_than", "min", "require"]).multiple(true),
+    name = "latest-version",
+    version = latest_version::VERSION,
+    about = "Find the latest version of commands across all available paths",
+    long_about = None,
+    after_help = "Exit status:\n  \
+        0    success; with several commands, at least one succeeded\n  \
+        1    any other failure, e.g. no version satisfies --min, --installed or --newer-than\n  \
+        3    the command was found, but no version could be read from it\n  \
+        127  the command is not on PATH"
+)]
+struct Args {
+    /// Commands to check for the latest version, each reported on its own
+    #[arg(
+        value_name = "COMMAND",
+        required_unless_present_any = ["clear_cache", "list_known"]
+    )]
+    commands: Vec<String>,
+
+    /// Reuse versions from the on-disk cache while executables are unchanged
+    #[arg(long, overrides_with = "no_cache")]
+    cache: bool,
+
+    /// Re-probe every executable instead of using the on-disk cache (the default)
+    #[arg(long, overrides_with = "cache")]
+    no_cache: bool,
+
+    /// Delete the on-disk version cache and exit
+    #[arg(long)]
+    clear_cache: bool,
+
+    /// Ignore pre-release versions (e.g. 2.0.0-rc1): select the newest stable one, and
+    /// leave them out of --all and --count
+    #[arg(long)]
+    stable_only: bool,
+
+    /// Print versions as reported (3.11 rather than 3.11.0) and count a component a
+    /// tool left out as unknown, so an explicit 3.11.0 or 3.11.2 wins over a bare 3.11
+    #[arg(long)]
+    partial_versions: bool,
+
+    /// Succeed only if the latest version is strictly newer than the executable at PATH
+    #[arg(long, value_name = "PATH")]
+    newer_than: Option<String>,
+
+    /// Print the --newer-than, --min or --require verdict as JSON instead of the path
+    #[arg(long, requires = "verdict", conflicts_with = "all")]
+    explain: bool,
+
+    /// List every executable found with its version, one per line
+    #[arg(long)]
+    all: bool,
+
+    /// Print the result as JSON: an object, or an array with --all
+    #[arg(long, conflicts_with = "explain")]
+    json: bool,
+
+    /// Print every executable found as a JSON object on its own line, as soon as it's probed
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "all", "json", "show_version", "version_only", "template", "first", "all_paths",
+            "count", "raw", "compare_active", "min", "require", "newer_than", "oldest"
+        ]
+    )]
+    jsonl: bool,
+
+    /// Print the selected path and its version, separated by a tab
+    #[arg(long, conflicts_with_all = ["json", "version_only"])]
+    show_version: bool,
+
+    /// Print only the selected version
+    #[arg(long, conflicts_with = "json")]
+    version_only: bool,
+
+    /// Print each result by filling in TEMPLATE's {path}, {version}, {major}, {minor},
+    /// {patch} and {dir}, e.g. '{version}\t{path}'; {{ and }} are literal braces
+    #[arg(
+        long = "format",
+        value_name = "TEMPLATE",
+        value_parser = parse_template,
+        conflicts_with_all = ["json", "show_version", "version_only"]
+    )]
+    template: Option<String>,
+
+    /// List the commands with built-in version flag handling and exit
+    #[arg(long)]
+    list_known: bool,
+
+    /// Warn about PATH entries that don't exist or aren't directories
+    #[arg(long)]
+    warn_missing_dirs: bool,
+
+    /// Log to stderr: -v for skipped executables and the selection, -vv for every
+    /// probe, -vvv also prints a per-directory scan report
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Version flag to try instead of the built-in guesses; repeat to try several in order
+    #[arg(long = "flag", value_name = "FLAG", allow_hyphen_values = true)]
+    flags: Vec<String>,
+
+    /// Arguments to try together as one probe, split on spaces, e.g. "version --short";
+    /// repeat to try several in order. Replaces --flag
+    #[arg(long = "probe", value_name = "ARGS", allow_hyphen_values = true)]
+    probes: Vec<String>,
+
+    /// Argument to pass before the version flag, e.g. --arg=-m --arg=pip to probe
+    /// "python -m pip --version"; repeat for several
+    #[arg(long = "arg", value_name = "ARG", allow_hyphen_values = true)]
+    args: Vec<String>,
+
+    /// Leave DIR, or anything symlinked to it, out of the PATH search; repeat to exclude several
+    #[arg(long = "exclude-dir", value_name = "DIR")]
+    exclude_dirs: Vec<PathBuf>,
+
+    /// Among equal versions, prefer the executable in DIR; repeat to rank several, highest first
+    #[arg(long = "prefer-dir", value_name = "DIR")]
+    prefer_dirs: Vec<PathBuf>,
+
+    /// Treat each COMMAND as a pattern matching executable names, with * for any run of
+    /// characters and ? for any one, e.g. 'python3*'
+    #[arg(long)]
+    glob: bool,
+
+    /// Also search up to DEPTH levels of subdirectories below each PATH entry, at most 8
+    #[arg(long, value_name = "DEPTH")]
+    recursive: Option<usize>,
+
+    /// Run each executable under this name instead of its path, e.g. wget to probe
+    /// that applet of a multicall binary like busybox
+    #[cfg(unix)]
+    #[arg(long, value_name = "NAME")]
+    arg0: Option<String>,
+
+    /// Kill a probe that runs for longer than this many milliseconds
+    #[arg(long, value_name = "MS")]
+    timeout: Option<u64>,
+
+    /// Print the first match on PATH without running anything to probe its version
+    #[arg(
+        long,
+        visible_alias = "path-order",
+        conflicts_with_all = [
+            "all", "json", "show_version", "version_only", "newer_than", "oldest", "min", "require"
+        ]
+    )]
+    first: bool,
+
+    /// List every executable that would be probed, with the command lines that would be
+    /// tried on it, without running anything
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "first", "all_paths", "all", "count", "jsonl", "json", "show_version", "version_only",
+            "newer_than", "oldest", "min", "require", "raw", "compare_active"
+        ]
+    )]
+    dry_run: bool,
+
+    /// List every match on PATH in order, like `which -a`, without running anything
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "first", "all", "count", "json", "show_version", "version_only", "newer_than",
+            "oldest", "min", "require", "raw", "compare_active"
+        ]
+    )]
+    all_paths: bool,
+
+    /// Print nothing, and exit 0 if any executable's version satisfies REQ, 1 if none
+    /// does, or 127 if the command isn't on PATH at all, e.g. "~3.11" for any 3.11.x
+    #[arg(
+        long,
+        value_name = "REQ",
+        conflicts_with_all = [
+            "all", "first", "all_paths", "count", "jsonl", "json", "show_version", "version_only",
+            "template", "newer_than", "oldest", "min", "require", "raw", "compare_active"
+        ]
+    )]
+    installed: Option<VersionReq>,
+
+    /// Print how many executables' versions could be read, and exit
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "all", "first", "json", "show_version", "version_only", "newer_than", "oldest", "raw"
+        ]
+    )]
+    count: bool,
+
+    /// With --count, count distinct versions rather than executables
+    #[arg(long, requires = "count")]
+    distinct: bool,
+
+    /// Also print the selected executable's raw version output to stderr
+    #[arg(long, conflicts_with_all = ["all", "first"])]
+    raw: bool,
+
+    /// Also report on stderr which executable the shell runs first, if it isn't the selected one
+    #[arg(long, conflicts_with_all = ["all", "first"])]
+    compare_active: bool,
+
+    /// Fail unless the selected version is at least the latest published one,
+    /// fetched as JSON from URL; "{command}" in URL is replaced with the command
+    #[cfg(feature = "registry")]
+    #[arg(long, value_name = "URL")]
+    check_outdated: Option<String>,
+
+    /// JSON pointer to the version in the --check-outdated response, e.g. /info/version
+    #[cfg(feature = "registry")]
+    #[arg(
+        long,
+        value_name = "POINTER",
+        default_value = "/version",
+        requires = "check_outdated"
+    )]
+    registry_pointer: String,
+
+    /// Select the oldest version instead of the newest
+    #[arg(long)]
+    oldest: bool,
+
+    /// Only consider executables at VERSION or newer, e.g. 3.10
+    #[arg(
+        long,
+        value_name = "VERSION",
+        value_parser = parse_min,
+        conflicts_with_all = ["require", "newer_than"]
+    )]
+    min: Option<VersionReq>,
+
+    /// Only consider executables whose version satisfies REQ, e.g. ">=3.10, <3.13" or "^3"
+    #[arg(long, value_name = "REQ", conflicts_with = "newer_than")]
+    require: Option<VersionReq>,
+
+    /// Take the version from the number following WORD, e.g. "release"
+    #[arg(long, value_name = "WORD")]
+    version_anchor: Option<String>,
+
+    /// Take the version from the group named "version" in PATTERN, e.g. 'v(?P<version>\d{4}w\d{2})'
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        value_parser = parse_regex,
+        conflicts_with_all = ["version_anchor", "scheme"]
+    )]
+    regex: Option<String>,
+
+    /// How to recognise versions in the probe output
+    #[arg(long, default_value_t = VersionScheme::Auto, value_parser = scheme_parser())]
+    scheme: VersionScheme,
+
+    /// Never color versions in human-readable output; NO_COLOR=1 does the same
+    #[arg(long)]
+    no_color: bool,
+
+    /// Order of the --all listing
+    #[arg(long, value_enum, default_value_t = SortArg::Version, requires = "all")]
+    sort: SortArg,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SortArg {
+    /// Newest version first
+    Version,
+    /// Lexicographically by path
+    Path,
+}
 
-    #[error("Failed to execute command {0}: {1}")]
-    CommandExecutionError(String, std::io::Error),
+/// Parses a [`VersionScheme`] by name, listing each one in `--help`.
+fn scheme_parser() -> impl TypedValueParser<Value = VersionScheme> {
+    PossibleValuesParser::new([
+        PossibleValue::new("semver")
+            .help("Strict major.minor.patch only; anything else reports no version"),
+        PossibleValue::new("lenient").help("The first dot-separated run of numbers, any length"),
+        PossibleValue::new("calver").help("Calendar versions such as 2023.11.04 or 20231104"),
+        PossibleValue::new("auto").help("Semver first, then progressively looser forms"),
+    ])
+    .try_map(|name| name.parse::<VersionScheme>())
+}
 
-    #[error("Version extraction failed: {0}")]
-    VersionExtractionError(String),
+impl From<SortArg> for SortOrder {
+    fn from(sort: SortArg) -> Self {
+        match sort {
+            SortArg::Version => SortOrder::Version,
+            SortArg::Path => SortOrder::Path,
+        }
+    }
+}
 
-    #[error("Failed to find executable paths")]
-    PathFindingError(String),
+fn parse_regex(pattern: &str) -> Result<String, regex::Error> {
+    regex::Regex::new(pattern).map(|_| pattern.to_string())
+}
 
-    #[error("Failed to parse version: {0}")]
-    VersionParsingError(#[from] semver::Error),
+fn parse_min(version: &str) -> Result<VersionReq, semver::Error> {
+    VersionReq::parse(&format!(">={}", version))
 }
 
-#[derive(Debug, Clone)]
-struct ExecutableInfo {
-    path: String,
-    version: String,
+/// How the selected executable is printed.
+#[derive(Clone, Copy, Debug)]
+enum OutputFormat<'a> {
+    Path,
+    PathAndVersion,
+    VersionOnly,
+    Json,
+    /// A `--format` template, already checked by [`parse_template`].
+    Template(&'a str),
 }
 
-fn find_executables(command: &str) -> Result<Vec<String>, LatestVersionError> {
-    let path =
-        std::env::var("PATH").map_err(|e| LatestVersionError::PathFindingError(e.to_string()))?;
+impl<'a> OutputFormat<'a> {
+    fn from_args(args: &'a Args) -> Self {
+        if args.json {
+            OutputFormat::Json
+        } else if let Some(template) = &args.template {
+            OutputFormat::Template(template)
+        } else if args.show_version {
+            OutputFormat::PathAndVersion
+        } else if args.version_only {
+            OutputFormat::VersionOnly
+        } else {
+            OutputFormat::Path
+        }
+    }
+}
 
-    let mut executables = Vec::new();
+/// Highlights for versions in human-readable output.
+#[derive(Clone, Copy, Debug)]
+enum Color {
+    /// The latest, or up to date.
+    Green,
+    /// Older than the latest.
+    Yellow,
+}
 
-    for dir in path.split(std::path::MAIN_SEPARATOR) {
-        if dir.is_empty() {
-            continue;
+impl Color {
+    /// `text` in this color as ANSI escapes, or unchanged unless `enabled`.
+    fn paint(self, text: &str, enabled: bool) -> String {
+        if !enabled {
+            return text.to_string();
         }
 
-        let command_path = std::path::Path::new(dir).join(command);
+        let code = match self {
+            Color::Green => 32,
+            Color::Yellow => 33,
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    }
+}
+
+/// Whether to color what's written to `stream`: only on a terminal, and
+/// never with `--no-color` or a non-empty `NO_COLOR` (see https://no-color.org).
+fn color_enabled(no_color: bool, stream: &impl IsTerminal) -> bool {
+    !no_color
+        && !matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty())
+        && stream.is_terminal()
+}
 
-        if command_path.is_file() && command_path.exists() {
-            match command_path.metadata() {
-                Ok(metadata) => {
-                    #[cfg(unix)]
-                    let is_executable = metadata.permissions().mode() & 0o111 != 0;
-                    #[cfg(windows)]
-                    let is_executable = true; // Assume all files are executable on Windows
+/// `template` with each `{field}` replaced by that field of `info`, `\t` and
+/// `\n` by a tab and a newline, and `{{` and `}}` by single braces. Fields that
+/// don't apply, like `{major}` of a version that isn't semver, are left empty.
+fn render_template(template: &str, info: &ExecutableInfo) -> Result<String, String> {
+    let mut rendered = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                rendered.push(c);
+                chars.next();
+            }
+            ('\\', Some('t')) => {
+                rendered.push('\t');
+                chars.next();
+            }
+            ('\\', Some('n')) => {
+                rendered.push('\n');
+                chars.next();
+            }
+            ('{', _) => {
+                let mut field = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    field.push(c);
+                }
+                if !closed {
+                    return Err(format!(
+                        "unclosed {{{}; write {{{{ for a literal brace",
+                        field
+                    ));
+                }
 
-                    if is_executable {
-                        if let Some(found_str) = command_path.to_str() {
-                            executables.push(found_str.to_string());
+                let number = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+                match field.as_str() {
+                    "path" => rendered.push_str(&info.path),
+                    "version" => rendered.push_str(&info.version),
+                    "major" => rendered.push_str(&number(info.major())),
+                    "minor" => rendered.push_str(&number(info.minor())),
+                    "patch" => rendered.push_str(&number(info.patch())),
+                    "dir" => {
+                        if let Some(dir) = info.dir() {
+                            rendered.push_str(&dir.to_string_lossy());
                         }
                     }
+                    _ => {
+                        return Err(format!(
+                            "unknown placeholder {{{}}}; expected one of {{path}}, {{version}}, \
+                             {{major}}, {{minor}}, {{patch}} or {{dir}}",
+                            field
+                        ))
+                    }
                 }
-                Err(_) => continue,
             }
+            ('}', _) => return Err("unmatched }; write }} for a literal brace".to_string()),
+            _ => rendered.push(c),
         }
     }
 
-    if executables.is_empty() {
-        if let Ok(found) = which(command) {
-            if let Some(found_str) = found.to_str() {
-                executables.push(found_str.to_string());
-            }
-        } else {
-            return Err(LatestVersionError::CommandNotFound(command.to_string()));
+    Ok(rendered)
+}
+
+/// Check a `--format` template up front, so a typo fails before anything is probed.
+fn parse_template(template: &str) -> Result<String, String> {
+    render_template(template, &ExecutableInfo::default())?;
+
+    Ok(template.to_string())
+}
+
+fn print_info(info: &ExecutableInfo, format: OutputFormat, color: bool) {
+    match format {
+        OutputFormat::Path => println!("{}", info.display_path()),
+        OutputFormat::PathAndVersion => println!(
+            "{}\t{}",
+            info.display_path(),
+            Color::Green.paint(&info.version, color)
+        ),
+        OutputFormat::VersionOnly => println!("{}", info.version),
+        OutputFormat::Json => println!("{}", serde_json::json!(info)),
+        OutputFormat::Template(template) => {
+            // Checked by `parse_template` when the arguments were parsed.
+            println!("{}", render_template(template, info).unwrap_or_default())
         }
     }
+}
 
-    Ok(executables)
+/// Print the probe that produced `info`'s version and its output, `raw`, as is.
+fn print_raw(info: &ExecutableInfo, raw: &str) {
+    eprint!("{}:\n{}", info.probe_command.join(" "), raw);
 }
 
-fn extract_version(output: &str) -> Option<String> {
-    let semver_pattern =
-        regex::Regex::new(r"(?P<major>\d+)\.(?P<minor>\d+)\.(?P<patch>\d+)").unwrap();
+/// Print whether `info` is also what the shell runs for `command`.
+fn compare_active(command: &str, info: &ExecutableInfo, probe: &ProbeOptions) -> Result<(), u8> {
+    let active = resolve_active_with(command, probe).map_err(|e| {
+        eprintln!("Error [{}]: {}", e.code(), e);
+        exit_code(&e)
+    })?;
 
-    if let Some(captures) = semver_pattern.captures(output) {
-        return Some(format!(
-            "{}.{}.{}",
-            &captures["major"], &captures["minor"], &captures["patch"]
-        ));
+    if active.path == info.path {
+        eprintln!("{} ({}) is the active executable", info.path, info.version);
+    } else {
+        eprintln!(
+            "{} ({}) is active instead of {} ({})",
+            active.path, active.version, info.path, info.version
+        );
     }
 
-    let minor_pattern = regex::Regex::new(r"(?P<major>\d+)\.(?P<minor>\d+)").unwrap();
+    Ok(())
+}
 
-    if let Some(captures) = minor_pattern.captures(output) {
-        return Some(format!("{}.{}.0", &captures["major"], &captures["minor"]));
-    }
+/// Compare `info` against the latest published version of `command`, failing if it's behind.
+#[cfg(feature = "registry")]
+fn check_outdated(
+    command: &str,
+    info: &ExecutableInfo,
+    url: &str,
+    pointer: &str,
+    color: bool,
+) -> Result<(), u8> {
+    let source = latest_version::RegistrySource::Json {
+        url: url.to_string(),
+        pointer: pointer.to_string(),
+    };
+    let published = match latest_version::latest_published(command, &source) {
+        Ok(published) => published,
+        Err(e) => {
+            eprintln!("Error [{}]: {}", e.code(), e);
+            return Err(exit_code(&e));
+        }
+    };
+    let outdated = compare_versions(&info.version, &published) == Ordering::Less;
+
+    eprintln!(
+        "{} ({}) is {} the latest published {}",
+        info.path,
+        if outdated {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+        .paint(&info.version, color),
+        if outdated {
+            "behind"
+        } else {
+            "up to date with"
+        },
+        published
+    );
 
-    let major_pattern = regex::Regex::new(r"(?P<major>\d+)").unwrap();
+    if outdated {
+        Err(1)
+    } else {
+        Ok(())
+    }
+}
 
-    if let Some(captures) = major_pattern.captures(output) {
-        return Some(format!("{}.0.0", &captures["major"]));
+fn find_latest(
+    command: &str,
+    use_cache: bool,
+    probe: &ProbeOptions,
+    options: &SelectionOptions,
+) -> Result<ExecutableInfo, LatestVersionError> {
+    // Custom probes bypass the cache, which only holds built-in probe results.
+    let cache_path = match VersionCache::default_path() {
+        Some(path) if use_cache => path,
+        _ => return find_latest_command_probed(command, probe, options),
+    };
+
+    // A missing or corrupt cache is not fatal; start afresh instead.
+    let mut cache = VersionCache::load(&cache_path).unwrap_or_default();
+    let result = find_latest_command_cached(command, &mut cache, probe, options);
+
+    if let Err(e) = cache.save(&cache_path) {
+        eprintln!("Warning: {}", e);
     }
 
-    None
+    result
 }
 
-fn get_version(executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
-    let mut command = Command::new(executable_path);
-    command.arg("--version");
+fn print_verdict(satisfied: bool, found: &str, required: &str) {
+    let verdict = serde_json::json!({
+        "satisfied": satisfied,
+        "found": found,
+        "required": required,
+    });
+    println!("{}", verdict);
+}
+
+fn main() -> std::process::ExitCode {
+    let args = Args::parse();
 
-    let output: Output = command
-        .output()
-        .map_err(|e| LatestVersionError::CommandExecutionError(executable_path.to_string(), e))?;
+    // RUST_LOG, if set, refines the level chosen by -v.
+    env_logger::Builder::new()
+        .filter_level(match args.verbose {
+            0 => log::LevelFilter::Error,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        })
+        .parse_default_env()
+        .init();
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let format = OutputFormat::from_args(&args);
 
-    let combined_output = format!("{}{}", stdout, stderr);
+    if args.list_known {
+        for (command, flags) in builtin_version_flags() {
+            println!("{}\t{}", command, flags.join(" "));
+        }
+        return std::process::ExitCode::SUCCESS;
+    }
 
-    if let Some(version_str) = extract_version(&combined_output) {
-        Ok(ExecutableInfo {
-            path: executable_path.to_string(),
-            version: version_str,
-        })
-    } else {
-        for flag in ["-v", "-V", "version"] {
-            let mut command = Command::new(executable_path);
-            command.arg(flag);
-
-            match command.output() {
-                Ok(output) => {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    let combined_output = format!("{}{}", stdout, stderr);
-
-                    if let Some(version_str) = extract_version(&combined_output) {
-                        return Ok(ExecutableInfo {
-                            path: executable_path.to_string(),
-                            version: version_str,
-                        });
-                    }
-                }
-                Err(_) => continue,
+    if args.clear_cache {
+        if let Some(cache_path) = VersionCache::default_path() {
+            if let Err(e) = VersionCache::clear(&cache_path) {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                return std::process::ExitCode::FAILURE;
             }
         }
 
-        Err(LatestVersionError::VersionExtractionError(
-            "No version information found".to_string(),
-        ))
+        if args.commands.is_empty() {
+            return std::process::ExitCode::SUCCESS;
+        }
     }
-}
 
-fn find_latest_version(
-    info_list: Vec<ExecutableInfo>,
-) -> Result<ExecutableInfo, LatestVersionError> {
-    let mut latest_info = None;
+    if args.warn_missing_dirs {
+        for dir in missing_path_dirs() {
+            eprintln!(
+                "Warning: PATH entry does not exist or is not a directory: {}",
+                dir.display()
+            );
+        }
+    }
 
+    let mut probe = ProbeOptions {
+        probes: args
+            .probes
+            .iter()
+            .map(|probe| probe.split_whitespace().map(str::to_string).collect())
+            .collect(),
+        args: args.args.clone(),
+        exclude_dirs: args.exclude_dirs.clone(),
+        search_depth: args.recursive.unwrap_or(0),
+        glob: args.glob,
+        ..Default::default()
+    };
+    if !args.flags.is_empty() {
+        probe.flags = args.flags.clone();
+    }
+    probe.extract.scheme = args.scheme;
+    #[cfg(unix)]
+    {
+        probe.arg0 = args.arg0.clone();
+    }
+    if args.partial_versions {
+        probe.extract.normalize = NormalizePolicy::Exact;
+    }
+    probe.extract.anchor = args.version_anchor.clone();
+    probe.extract.regex = args.regex.clone();
+    if let Some(timeout) = args.timeout {
+        probe.timeout = Some(Duration::from_millis(timeout));
+    }
+
+    // Each command is reported on its own; only fail if none of them succeeded,
+    // and then with their shared exit code if they agree on one.
+    let mut failures = Vec::new();
+    for command in &args.commands {
+        if let Err(code) = report(command, &args, &probe, format) {
+            failures.push(code);
+        }
+    }
+
+    if failures.len() < args.commands.len() {
+        return std::process::ExitCode::SUCCESS;
+    }
+
+    match failures.split_first() {
+        Some((&code, rest)) if rest.iter().all(|&other| other == code) => code.into(),
+        _ => std::process::ExitCode::FAILURE,
+    }
+}
+
+/// How many different versions `info_list` holds, counting e.g. `1.2` and
+/// `1.2.0` as one.
+fn distinct_versions(info_list: &[ExecutableInfo]) -> usize {
+    let mut versions: Vec<&str> = Vec::new();
     for info in info_list {
-        match Version::parse(&info.version) {
-            Ok(parsed_version) => match &latest_info {
-                None => latest_info = Some(info),
-                Some(latest) => match Version::parse(&latest.version) {
-                    Ok(latest_version) => {
-                        if parsed_version > latest_version {
-                            latest_info = Some(info);
-                        }
-                    }
-                    Err(_) => {
-                        latest_info = Some(info);
-                    }
-                },
-            },
-            Err(_) => match &latest_info {
-                None => latest_info = Some(info),
-                Some(latest) => match version_compare::compare(&info.version, &latest.version) {
-                    Ok(Cmp::Gt) => latest_info = Some(info),
-                    _ => continue,
-                },
-            },
+        if !versions
+            .iter()
+            .any(|version| compare_versions(version, &info.version) == Ordering::Equal)
+        {
+            versions.push(&info.version);
         }
     }
 
-    latest_info.ok_or(LatestVersionError::VersionExtractionError(
-        "No valid versions found".to_string(),
-    ))
+    versions.len()
 }
 
-fn find_latest_command(command: &str) -> Result<ExecutableInfo, LatestVersionError> {
-    let executables = find_executables(command)?;
+/// The exit status for a failed lookup, as listed in `--help`.
+fn exit_code(error: &LatestVersionError) -> u8 {
+    match error {
+        LatestVersionError::CommandNotFound(_) => 127,
+        LatestVersionError::VersionExtractionError(_)
+        | LatestVersionError::NoVersionInOutput { .. }
+        | LatestVersionError::SilentFailure { .. } => 3,
+        _ => 1,
+    }
+}
+
+/// Find and print the latest `command`, or the exit code to fail with.
+fn report(
+    command: &str,
+    args: &Args,
+    probe: &ProbeOptions,
+    format: OutputFormat,
+) -> Result<(), u8> {
+    if args.verbose >= 3 {
+        eprint!("{}", scan_path(command));
+    }
+
+    if args.dry_run {
+        return match plan_probes(command, probe) {
+            Ok(plan) => {
+                for planned in plan {
+                    print!("{}", planned);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                Err(exit_code(&e))
+            }
+        };
+    }
+
+    if args.first || args.all_paths {
+        // Executables are listed in PATH order, so the first is the one `which` would pick.
+        return match find_executables_with(command, probe) {
+            Ok(executables) => {
+                let shown = if args.first { 1 } else { executables.len() };
+                for executable in &executables[..shown] {
+                    println!("{}", executable);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                Err(exit_code(&e))
+            }
+        };
+    }
+
+    if args.jsonl {
+        let streamed = find_all_versions_streamed(command, probe, |info| {
+            // One locked write per line keeps lines from parallel probes whole.
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{}", serde_json::json!(info));
+            let _ = stdout.flush();
+        });
+        return streamed.map(|_| ()).map_err(|e| {
+            eprintln!("Error [{}]: {}", e.code(), e);
+            exit_code(&e)
+        });
+    }
+
+    if let Some(requirement) = &args.installed {
+        return match is_installed_with(command, requirement, probe) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(1),
+            Err(e) => {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                Err(exit_code(&e))
+            }
+        };
+    }
 
-    let mut info_list = Vec::new();
+    let requirement = args.min.as_ref().or(args.require.as_ref());
+    let color = color_enabled(args.no_color, &std::io::stdout());
+
+    if args.count {
+        let info_list = match find_all_versions_with(command, probe) {
+            Ok(info_list) => info_list,
+            // Nothing installed is a count like any other.
+            Err(LatestVersionError::CommandNotFound(_)) => Vec::new(),
+            Err(e) => {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                return Err(exit_code(&e));
+            }
+        };
+        let mut info_list = match requirement {
+            Some(requirement) => select_matching(info_list, requirement),
+            None => info_list,
+        };
+        if args.stable_only {
+            info_list.retain(|info| !info.is_prerelease());
+        }
 
-    for executable in executables {
-        match get_version(&executable) {
-            Ok(info) => info_list.push(info),
-            Err(_) => continue,
+        if args.distinct {
+            println!("{}", distinct_versions(&info_list));
+        } else {
+            println!("{}", info_list.len());
         }
+        return Ok(());
     }
 
-    if info_list.is_empty() {
-        return Err(LatestVersionError::VersionExtractionError(format!(
-            "No version information found for command '{}'",
-            command
-        )));
+    let options = SelectionOptions {
+        exclude_prereleases: args.stable_only,
+        oldest: args.oldest,
+        prefer_dirs: args.prefer_dirs.clone(),
+        partial_versions: args.partial_versions,
+        ..Default::default()
+    };
+
+    if args.all || requirement.is_some() {
+        let mut raw_outputs = HashMap::new();
+        // With --verbose, the listing also says why anything else was skipped.
+        let report_failures = args.all && args.verbose > 0 && !args.json;
+        let probed = if report_failures {
+            probe_all_with(command, probe).map(|results| {
+                let (found, failed): (Vec<_>, Vec<_>) = results
+                    .into_iter()
+                    .partition(|result| result.version.is_ok());
+                let info_list = found
+                    .into_iter()
+                    .filter_map(|result| {
+                        Some(ExecutableInfo::new(result.path, result.version.ok()?))
+                    })
+                    .collect();
+                (info_list, failed)
+            })
+        } else if args.raw {
+            // Keep each probe's output, so --raw shows what the version was read from.
+            find_all_versions_verbose_with(command, probe).map(|probed| {
+                let info_list = probed
+                    .into_iter()
+                    .map(|(info, raw)| {
+                        raw_outputs.insert(info.path.clone(), raw);
+                        info
+                    })
+                    .collect();
+                (info_list, Vec::new())
+            })
+        } else {
+            find_all_versions_with(command, probe).map(|info_list| (info_list, Vec::new()))
+        };
+
+        return match probed {
+            Ok((mut info_list, failed)) => {
+                if args.stable_only {
+                    info_list.retain(|info| !info.is_prerelease());
+                }
+                if let Some(requirement) = requirement {
+                    let matching = select_matching(info_list.clone(), requirement);
+                    if args.explain {
+                        // Unsatisfied, the verdict reports the best version there is instead.
+                        let satisfied = !matching.is_empty();
+                        let candidates = if satisfied { matching } else { info_list };
+                        let found = find_latest_version_with(candidates, &options)
+                            .map(|info| info.version)
+                            .unwrap_or_default();
+                        print_verdict(satisfied, &found, &requirement.to_string());
+                        return if satisfied { Ok(()) } else { Err(1) };
+                    }
+                    if matching.is_empty() {
+                        let e = LatestVersionError::NoMatchingVersion {
+                            command: command.to_string(),
+                            requirement: requirement.to_string(),
+                        };
+                        eprintln!("Error [{}]: {}", e.code(), e);
+                        return Err(exit_code(&e));
+                    }
+                    info_list = matching;
+                }
+
+                if !args.all {
+                    return match find_latest_version_with(info_list, &options) {
+                        Ok(info) => {
+                            print_info(&info, format, color);
+                            if let Some(raw) = raw_outputs.get(&info.path) {
+                                print_raw(&info, raw);
+                            }
+                            Ok(())
+                        }
+                        Err(e) => {
+                            eprintln!("Error [{}]: {}", e.code(), e);
+                            Err(exit_code(&e))
+                        }
+                    };
+                }
+
+                sort_executables(&mut info_list, args.sort.into());
+                if args.json {
+                    println!("{}", serde_json::json!(info_list));
+                } else {
+                    let latest = info_list
+                        .iter()
+                        .map(|info| info.version.as_str())
+                        .max_by(|a, b| compare_versions(a, b))
+                        .unwrap_or_default();
+                    for info in &info_list {
+                        if let OutputFormat::Template(_) = format {
+                            print_info(info, format, color);
+                            continue;
+                        }
+                        let highlight =
+                            if compare_versions(&info.version, latest) == Ordering::Equal {
+                                Color::Green
+                            } else {
+                                Color::Yellow
+                            };
+                        println!(
+                            "{}\t{}",
+                            info.display_path(),
+                            highlight.paint(&info.version, color)
+                        );
+                    }
+                    for result in &failed {
+                        if let Err(e) = &result.version {
+                            println!("{}\tError [{}]: {}", result.path, e.code(), e);
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error [{}]: {}", e.code(), e);
+                Err(exit_code(&e))
+            }
+        };
     }
 
-    find_latest_version(info_list)
-}
+    let found = if args.raw {
+        find_latest_command_verbose_with(command, probe, &options)
+            .map(|(info, raw)| (info, Some(raw)))
+    } else {
+        find_latest(command, args.cache, probe, &options).map(|info| (info, None))
+    };
+
+    match found {
+        Ok((info, raw)) => {
+            if let Some(reference) = &args.newer_than {
+                // The selected executable was just probed; only the reference is new.
+                let reference = match get_version_with(reference, probe) {
+                    Ok(reference) => reference,
+                    Err(e) => {
+                        eprintln!("Error [{}]: {}", e.code(), e);
+                        return Err(exit_code(&e));
+                    }
+                };
+                let latest = &info;
+                let satisfied =
+                    compare_versions(&latest.version, &reference.version) == Ordering::Greater;
+
+                if args.explain {
+                    print_verdict(
+                        satisfied,
+                        &latest.version,
+                        &format!(">{}", reference.version),
+                    );
+                } else {
+                    eprintln!(
+                        "{} ({}) is {}newer than {} ({})",
+                        latest.path,
+                        latest.version,
+                        if satisfied { "" } else { "not " },
+                        reference.path,
+                        reference.version
+                    );
+                }
 
-#[derive(Parser, Debug)]
-#[command(
-    name = "latest-version",
-    version = "0.1.0",
-    about = "Find the latest version of commands across all available paths",
-    long_about = None
-)]
-struct Args {
-    /// Command to check for latest version
-    #[arg(value_name = "COMMAND")]
-    command: String,
-}
+                if !satisfied {
+                    return Err(1);
+                }
 
-fn main() -> std::process::ExitCode {
-    let args = Args::parse();
+                if args.explain {
+                    return Ok(());
+                }
+            }
+
+            print_info(&info, format, color);
+            if let Some(raw) = &raw {
+                print_raw(&info, raw);
+            }
+            if args.compare_active {
+                compare_active(command, &info, probe)?;
+            }
+
+            #[cfg(feature = "registry")]
+            if let Some(url) = &args.check_outdated {
+                return check_outdated(
+                    command,
+                    &info,
+                    url,
+                    &args.registry_pointer,
+                    color_enabled(args.no_color, &std::io::stderr()),
+                );
+            }
 
-    match find_latest_command(&args.command) {
-        Ok(info) => {
-            println!("{}", info.path);
-            std::process::ExitCode::SUCCESS
+            Ok(())
         }
         Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::ExitCode::FAILURE
+            eprintln!("Error [{}]: {}", e.code(), e);
+            Err(exit_code(&e))
         }
     }
 }
@@ -0,0 +1,277 @@
+use crate::{
+    find_executables, get_version, probe_latest, probe_version, run_command, run_probe,
+    version_flags_for, ExecutableInfo, ExtractOptions, LatestVersionError, ProbeOptions,
+    SelectionOptions,
+};
+use std::process::{Command, Output};
+use std::time::Duration;
+
+/// Where executables are looked up and how they are run.
+///
+/// [`LocalProvider`] searches this machine's `PATH`; [`PrefixProvider`] runs every
+/// lookup and probe through a command prefix such as `ssh host` or
+/// `docker exec container`. Implement this trait to plug in anything else.
+pub trait Provider {
+    /// The directories searched for executables, in priority order.
+    fn search_dirs(&self) -> Result<Vec<String>, LatestVersionError>;
+
+    /// Whether `path` names an executable file.
+    fn is_executable(&self, path: &str) -> bool;
+
+    /// Run `executable_path flag` and return its combined stdout and stderr.
+    fn run(&self, executable_path: &str, flag: &str) -> std::io::Result<String>;
+
+    /// Every executable named `command` in [`Provider::search_dirs`], in order.
+    fn find_executables(&self, command: &str) -> Result<Vec<String>, LatestVersionError> {
+        let executables: Vec<String> = self
+            .search_dirs()?
+            .iter()
+            .map(|dir| format!("{}/{}", dir.trim_end_matches('/'), command))
+            .filter(|candidate| self.is_executable(candidate))
+            .collect();
+
+        if executables.is_empty() {
+            return Err(LatestVersionError::CommandNotFound(command.to_string()));
+        }
+
+        Ok(executables)
+    }
+
+    /// Probe `executable_path` for its version using [`Provider::run`].
+    fn get_version(&self, executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
+        probe_version(
+            executable_path,
+            &version_flags_for(executable_path),
+            &ExtractOptions::default(),
+            |flag| self.run(executable_path, flag),
+        )
+    }
+}
+
+/// The default provider: this machine's `PATH`, probed directly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalProvider;
+
+impl Provider for LocalProvider {
+    fn search_dirs(&self) -> Result<Vec<String>, LatestVersionError> {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+
+        Ok(std::env::split_paths(&path)
+            .filter_map(|dir| dir.to_str().map(str::to_string))
+            .collect())
+    }
+
+    fn is_executable(&self, path: &str) -> bool {
+        crate::is_executable_file(std::path::Path::new(path))
+    }
+
+    fn run(&self, executable_path: &str, flag: &str) -> std::io::Result<String> {
+        let output = run_probe(executable_path, &[flag], &ProbeOptions::default())?;
+
+        Ok(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+
+    fn find_executables(&self, command: &str) -> Result<Vec<String>, LatestVersionError> {
+        find_executables(command)
+    }
+
+    fn get_version(&self, executable_path: &str) -> Result<ExecutableInfo, LatestVersionError> {
+        get_version(executable_path)
+    }
+}
+
+/// Runs every lookup and probe as `<prefix> <args...>`, e.g. with a prefix of
+/// `["docker", "exec", "web"]` or `["ssh", "build-host"]`.
+///
+/// The target is assumed to be a POSIX system with `printenv` and `test`. Only
+/// absolute entries of its `PATH` are searched. Arguments are passed through
+/// unquoted, so with `ssh` they are re-split by the remote shell.
+///
+/// Each remote command, including the `PATH` lookup, is killed after the
+/// timeout from [`ProbeOptions::default`].
+#[derive(Debug, Clone)]
+pub struct PrefixProvider {
+    prefix: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+impl PrefixProvider {
+    pub fn new<I, S>(prefix: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            prefix: prefix.into_iter().map(Into::into).collect(),
+            timeout: ProbeOptions::default().timeout,
+        }
+    }
+
+    /// Kill each remote command that runs for longer than `timeout`.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut parts = self
+            .prefix
+            .iter()
+            .map(String::as_str)
+            .chain(args.iter().copied());
+
+        // `args` is never empty, so there is always a program to run.
+        let mut command = Command::new(parts.next().unwrap_or_default());
+        command.args(parts);
+        command
+    }
+
+    /// Run `<prefix> <args...>`, killing it after the timeout, e.g. when `ssh`
+    /// is stuck at a password prompt.
+    fn output(&self, args: &[&str]) -> std::io::Result<Output> {
+        let options = ProbeOptions {
+            timeout: self.timeout,
+            ..Default::default()
+        };
+        run_command(self.command(args), &options)
+    }
+}
+
+impl Provider for PrefixProvider {
+    fn search_dirs(&self) -> Result<Vec<String>, LatestVersionError> {
+        let output = self
+            .output(&["printenv", "PATH"])
+            .map_err(|e| LatestVersionError::PathFindingError(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(LatestVersionError::PathFindingError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .split(':')
+            .filter(|dir| dir.starts_with('/'))
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn is_executable(&self, path: &str) -> bool {
+        self.output(&["test", "-x", path])
+            .is_ok_and(|output| output.status.success())
+    }
+
+    fn run(&self, executable_path: &str, flag: &str) -> std::io::Result<String> {
+        let output = self.output(&[executable_path, flag])?;
+
+        Ok(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+/// Like [`find_latest_command`](crate::find_latest_command), but looks up and
+/// probes `command` through `provider`.
+pub fn find_latest_command_via<P>(
+    provider: &P,
+    command: &str,
+) -> Result<ExecutableInfo, LatestVersionError>
+where
+    P: Provider + ?Sized,
+{
+    probe_latest(
+        command,
+        provider.find_executables(command)?,
+        |executable| provider.get_version(executable),
+        &SelectionOptions::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// A remote host whose executables and their `--version` output are canned.
+    struct MockRemote {
+        dirs: Vec<String>,
+        outputs: HashMap<String, String>,
+    }
+
+    impl Provider for MockRemote {
+        fn search_dirs(&self) -> Result<Vec<String>, LatestVersionError> {
+            Ok(self.dirs.clone())
+        }
+
+        fn is_executable(&self, path: &str) -> bool {
+            self.outputs.contains_key(path)
+        }
+
+        fn run(&self, executable_path: &str, flag: &str) -> std::io::Result<String> {
+            match self.outputs.get(executable_path) {
+                Some(output) if flag == "--version" => Ok(output.clone()),
+                Some(_) => Ok(String::new()),
+                None => Err(std::io::ErrorKind::NotFound.into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mock_remote_pipeline() {
+        let remote = MockRemote {
+            dirs: vec![
+                "/usr/local/bin/".into(),
+                "/usr/bin".into(),
+                "/opt/empty".into(),
+            ],
+            outputs: [
+                ("/usr/local/bin/node", "v18.19.0"),
+                ("/usr/bin/node", "v20.11.1"),
+                ("/usr/bin/npm", "10.2.4"),
+            ]
+            .into_iter()
+            .map(|(path, output)| (path.to_string(), output.to_string()))
+            .collect(),
+        };
+
+        assert_eq!(
+            remote.find_executables("node").unwrap(),
+            ["/usr/local/bin/node", "/usr/bin/node"]
+        );
+
+        let latest = find_latest_command_via(&remote, "node").unwrap();
+        assert_eq!(latest.path, "/usr/bin/node");
+        assert_eq!(latest.version, "20.11.1");
+        assert_eq!(latest.probe_command, ["/usr/bin/node", "--version"]);
+
+        assert!(matches!(
+            find_latest_command_via(&remote, "python3"),
+            Err(LatestVersionError::CommandNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prefix_probe_is_killed_at_the_timeout() {
+        // The prefix ignores the probed path and flag and just hangs.
+        let mut provider = PrefixProvider::new(["sh", "-c", "sleep 5", "sh"]);
+        provider.timeout(Duration::from_millis(100));
+
+        let started = std::time::Instant::now();
+        let error = provider.run("/bin/true", "--version").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+        assert!(matches!(
+            provider.search_dirs(),
+            Err(LatestVersionError::PathFindingError(_))
+        ));
+        assert!(!provider.is_executable("/bin/true"));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
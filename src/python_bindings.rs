@@ -1,61 +1,235 @@
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
+/// Python exceptions for each kind of [`LatestVersionError`], all subclasses of
+/// `latest_version.LatestVersionError`, which is itself a `RuntimeError`.
+#[cfg(feature = "pyo3")]
+mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyRuntimeError;
+
+    create_exception!(_latest_version, LatestVersionError, PyRuntimeError);
+    create_exception!(_latest_version, CommandNotFoundError, LatestVersionError);
+    create_exception!(_latest_version, CommandExecutionError, LatestVersionError);
+    create_exception!(_latest_version, VersionExtractionError, LatestVersionError);
+    create_exception!(_latest_version, VersionParsingError, LatestVersionError);
+    create_exception!(_latest_version, ProbeTimeoutError, LatestVersionError);
+}
+
+#[cfg(feature = "pyo3")]
+impl From<LatestVersionError> for PyErr {
+    fn from(error: LatestVersionError) -> Self {
+        let message = error.to_string();
+        match error {
+            LatestVersionError::CommandNotFound(_) => {
+                exceptions::CommandNotFoundError::new_err(message)
+            }
+            LatestVersionError::CommandExecutionError(..) => {
+                exceptions::CommandExecutionError::new_err(message)
+            }
+            LatestVersionError::VersionExtractionError(_)
+            | LatestVersionError::NoVersionInOutput { .. }
+            | LatestVersionError::SilentFailure { .. } => {
+                exceptions::VersionExtractionError::new_err(message)
+            }
+            LatestVersionError::VersionParsingError(_) => {
+                exceptions::VersionParsingError::new_err(message)
+            }
+            LatestVersionError::Timeout(_) => exceptions::ProbeTimeoutError::new_err(message),
+            _ => exceptions::LatestVersionError::new_err(message),
+        }
+    }
+}
+
 #[cfg(feature = "pyo3")]
 #[pyclass]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PyExecutableInfo {
     #[pyo3(get)]
     pub path: String,
     #[pyo3(get)]
     pub version: String,
+    #[pyo3(get)]
+    pub probe_command: Vec<String>,
 }
 
+/// Equality and hashing use both path and version. Ordering is by version, using
+/// the same comparison as [`compare_versions`], then by path, so `sorted()` on a
+/// list of these puts the oldest version first.
 #[cfg(feature = "pyo3")]
 #[pymethods]
 impl PyExecutableInfo {
     #[new]
     fn new(path: String, version: String) -> Self {
-        Self { path, version }
+        Self {
+            path,
+            version,
+            probe_command: Vec::new(),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ExecutableInfo(path={:?}, version={:?})",
+            self.path, self.version
+        )
+    }
+
+    fn __str__(&self) -> String {
+        format!("{} ({})", self.path, self.version)
+    }
+
+    fn __hash__(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (&self.path, &self.version).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __richcmp__(
+        &self,
+        other: &Bound<'_, PyAny>,
+        op: pyo3::basic::CompareOp,
+        py: Python<'_>,
+    ) -> PyObject {
+        let Ok(other) = other.extract::<PyRef<'_, PyExecutableInfo>>() else {
+            return py.NotImplemented();
+        };
+
+        let result = match op {
+            pyo3::basic::CompareOp::Eq => self.path == other.path && self.version == other.version,
+            pyo3::basic::CompareOp::Ne => self.path != other.path || self.version != other.version,
+            _ => op.matches(
+                compare_versions(&self.version, &other.version)
+                    .then_with(|| self.path.cmp(&other.path)),
+            ),
+        };
+
+        result.into_py(py)
+    }
+}
+
+#[cfg(feature = "pyo3")]
+impl From<ExecutableInfo> for PyExecutableInfo {
+    fn from(info: ExecutableInfo) -> Self {
+        Self {
+            path: info.path,
+            version: info.version,
+            probe_command: info.probe_command,
+        }
     }
 }
 
+#[cfg(feature = "pyo3")]
+#[pyclass]
+#[derive(Debug)]
+pub struct PyLatestReport {
+    #[pyo3(get)]
+    pub latest: Option<PyExecutableInfo>,
+    #[pyo3(get)]
+    pub failures: Vec<(String, String)>,
+}
+
 #[cfg(feature = "pyo3")]
 #[pyfunction]
 fn find_executables_py(command: &str) -> PyResult<Vec<String>> {
-    find_executables(command)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+    Ok(find_executables(command)?)
 }
 
 #[cfg(feature = "pyo3")]
 #[pyfunction]
 fn get_version_py(executable_path: &str) -> PyResult<PyExecutableInfo> {
     get_version(executable_path)
-        .map(|info| PyExecutableInfo {
-            path: info.path,
-            version: info.version,
-        })
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+        .map(PyExecutableInfo::from)
+        .map_err(PyErr::from)
 }
 
 #[cfg(feature = "pyo3")]
 #[pyfunction]
 fn find_latest_command_py(command: &str) -> PyResult<PyExecutableInfo> {
     find_latest_command(command)
-        .map(|info| PyExecutableInfo {
-            path: info.path,
-            version: info.version,
+        .map(PyExecutableInfo::from)
+        .map_err(PyErr::from)
+}
+
+#[cfg(feature = "pyo3")]
+#[pyfunction]
+fn find_all_versions_py(command: &str) -> PyResult<Vec<PyExecutableInfo>> {
+    find_all_versions(command)
+        .map(|info_list| info_list.into_iter().map(PyExecutableInfo::from).collect())
+        .map_err(PyErr::from)
+}
+
+#[cfg(feature = "pyo3")]
+#[pyfunction]
+fn find_latest_command_detailed_py(command: &str) -> PyResult<PyLatestReport> {
+    find_latest_command_detailed(command)
+        .map(|report| PyLatestReport {
+            latest: report.latest.map(PyExecutableInfo::from),
+            failures: report
+                .failures
+                .into_iter()
+                .map(|(path, e)| (path, e.to_string()))
+                .collect(),
         })
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+        .map_err(PyErr::from)
+}
+
+/// Await the latest version of `command` without blocking the event loop; the
+/// probes run on tokio's blocking thread pool.
+#[cfg(all(feature = "pyo3", feature = "tokio"))]
+#[pyfunction]
+fn find_latest_command_async_py(py: Python<'_>, command: String) -> PyResult<Bound<'_, PyAny>> {
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        tokio::task::spawn_blocking(move || find_latest_command(&command))
+            .await
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?
+            .map(PyExecutableInfo::from)
+            .map_err(PyErr::from)
+    })
 }
 
 #[cfg(feature = "pyo3")]
 #[pymodule]
 fn _latest_version(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyExecutableInfo>()?;
+    m.add_class::<PyLatestReport>()?;
+    m.add("__version__", VERSION)?;
+
+    let py = m.py();
+    m.add(
+        "LatestVersionError",
+        py.get_type_bound::<exceptions::LatestVersionError>(),
+    )?;
+    m.add(
+        "CommandNotFoundError",
+        py.get_type_bound::<exceptions::CommandNotFoundError>(),
+    )?;
+    m.add(
+        "CommandExecutionError",
+        py.get_type_bound::<exceptions::CommandExecutionError>(),
+    )?;
+    m.add(
+        "VersionExtractionError",
+        py.get_type_bound::<exceptions::VersionExtractionError>(),
+    )?;
+    m.add(
+        "VersionParsingError",
+        py.get_type_bound::<exceptions::VersionParsingError>(),
+    )?;
+    m.add(
+        "ProbeTimeoutError",
+        py.get_type_bound::<exceptions::ProbeTimeoutError>(),
+    )?;
+
     m.add_function(wrap_pyfunction!(find_executables_py, m)?)?;
     m.add_function(wrap_pyfunction!(get_version_py, m)?)?;
     m.add_function(wrap_pyfunction!(find_latest_command_py, m)?)?;
+    m.add_function(wrap_pyfunction!(find_all_versions_py, m)?)?;
+    m.add_function(wrap_pyfunction!(find_latest_command_detailed_py, m)?)?;
+    #[cfg(feature = "tokio")]
+    m.add_function(wrap_pyfunction!(find_latest_command_async_py, m)?)?;
 
     Ok(())
 }
@@ -0,0 +1,125 @@
+use crate::{extract_version, LatestVersionError};
+
+/// Where [`latest_published`] looks up the newest released version of a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistrySource {
+    /// Fetch `url` and read the version from the JSON it returns. `{command}`
+    /// in the URL is replaced with the command name, and `pointer` is a JSON
+    /// pointer to the version string, e.g. `/info/version` for
+    /// `https://pypi.org/pypi/{command}/json`.
+    Json { url: String, pointer: String },
+}
+
+/// The latest published version of `command` according to `source`,
+/// normalised the same way as installed versions so the two can be compared
+/// with [`compare_versions`](crate::compare_versions).
+pub fn latest_published(
+    command: &str,
+    source: &RegistrySource,
+) -> Result<String, LatestVersionError> {
+    match source {
+        RegistrySource::Json { url, pointer } => {
+            let url = url.replace("{command}", command);
+            let registry_error = |e: &dyn std::fmt::Display| {
+                LatestVersionError::RegistryError(format!("{}: {}", url, e))
+            };
+            let body = ureq::get(&url)
+                .call()
+                .map_err(|e| registry_error(&e))?
+                .into_string()
+                .map_err(|e| registry_error(&e))?;
+
+            version_at(&body, pointer).ok_or_else(|| {
+                LatestVersionError::RegistryError(format!("{}: no version at {}", url, pointer))
+            })
+        }
+    }
+}
+
+fn version_at(body: &str, pointer: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+    match json.pointer(pointer)? {
+        serde_json::Value::String(version) => extract_version(version),
+        serde_json::Value::Number(version) => extract_version(&version.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve `body` as JSON to a single request, returning the server's base
+    /// URL and a handle yielding the request line it received.
+    fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            for line in reader.lines() {
+                if line.unwrap().is_empty() {
+                    break;
+                }
+            }
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            request_line
+        });
+
+        (url, server)
+    }
+
+    #[test]
+    fn test_latest_published_reads_the_pointer() {
+        let (url, server) = serve_once("200 OK", r#"{"info": {"version": "v3.12.1"}}"#);
+        let source = RegistrySource::Json {
+            url: format!("{}/pypi/{{command}}/json", url),
+            pointer: "/info/version".to_string(),
+        };
+
+        assert_eq!(latest_published("tool", &source).unwrap(), "3.12.1");
+        assert!(server.join().unwrap().starts_with("GET /pypi/tool/json "));
+    }
+
+    #[test]
+    fn test_latest_published_reports_bad_responses() {
+        let (url, server) = serve_once("200 OK", r#"{"info": {}}"#);
+        let source = RegistrySource::Json {
+            url,
+            pointer: "/info/version".to_string(),
+        };
+        assert!(matches!(
+            latest_published("tool", &source),
+            Err(LatestVersionError::RegistryError(_))
+        ));
+        server.join().unwrap();
+
+        let (url, server) = serve_once("404 Not Found", "{}");
+        let source = RegistrySource::Json {
+            url,
+            pointer: "/version".to_string(),
+        };
+        assert!(matches!(
+            latest_published("tool", &source),
+            Err(LatestVersionError::RegistryError(_))
+        ));
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,1259 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+fn write_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn latest_version(path_dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("latest-version").unwrap();
+    cmd.env("PATH", path_dir).arg("--no-cache");
+    cmd
+}
+
+#[test]
+fn newer_than_older_reference_succeeds() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let ref_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 2.1.0'");
+    let reference = write_script(ref_dir.path(), "tool", "echo 'tool 2.0.3'");
+
+    latest_version(path_dir.path())
+        .args(["--newer-than", reference.to_str().unwrap(), "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+}
+
+#[test]
+fn newer_than_same_or_newer_reference_fails() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let ref_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 2.1.0'");
+    let same = write_script(ref_dir.path(), "same", "echo 'tool 2.1.0'");
+    let newer = write_script(ref_dir.path(), "newer", "echo 'tool 3.0.0'");
+
+    for reference in [same, newer] {
+        latest_version(path_dir.path())
+            .args(["--newer-than", reference.to_str().unwrap(), "tool"])
+            .assert()
+            .failure()
+            .stdout("");
+    }
+}
+
+#[test]
+fn newer_than_probes_the_reference_like_the_candidates() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let ref_dir = tempfile::tempdir().unwrap();
+    let answers = |short: &str, long: &str| {
+        format!(
+            "case \"$1\" in -V) echo 'tool {}' ;; *) echo 'tool {}' ;; esac",
+            short, long
+        )
+    };
+    let tool = write_script(path_dir.path(), "tool", &answers("1.0.0", "0.1.0"));
+    let reference = write_script(ref_dir.path(), "tool", &answers("0.5.0", "3.0.0"));
+
+    let assert = latest_version(path_dir.path())
+        .args([
+            "--flag=-V",
+            "--newer-than",
+            reference.to_str().unwrap(),
+            "tool",
+        ])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(stderr.contains("(1.0.0) is newer than"), "{}", stderr);
+    assert!(stderr.contains("(0.5.0)"), "{}", stderr);
+}
+
+#[test]
+fn explain_prints_newer_than_verdict() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let ref_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 3.11.0'");
+    let older = write_script(ref_dir.path(), "older", "echo 'tool 3.10.2'");
+    let newer = write_script(ref_dir.path(), "newer", "echo 'tool 3.12.0'");
+
+    for (reference, satisfied, required) in [(older, true, ">3.10.2"), (newer, false, ">3.12.0")] {
+        let assert = latest_version(path_dir.path())
+            .args([
+                "--explain",
+                "--newer-than",
+                reference.to_str().unwrap(),
+                "tool",
+            ])
+            .assert()
+            .code(if satisfied { 0 } else { 1 });
+
+        let verdict: serde_json::Value =
+            serde_json::from_slice(&assert.get_output().stdout).unwrap();
+        assert_eq!(verdict["satisfied"], satisfied);
+        assert_eq!(verdict["found"], "3.11.0");
+        assert_eq!(verdict["required"], required);
+    }
+}
+
+#[test]
+fn explain_prints_requirement_verdict() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 3.11.0'");
+
+    for (flag, value, satisfied, required) in [
+        ("--min", "3.10", true, ">=3.10"),
+        ("--min", "3.12", false, ">=3.12"),
+        ("--require", "^3.11", true, "^3.11"),
+    ] {
+        let assert = latest_version(path_dir.path())
+            .args(["--explain", flag, value, "tool"])
+            .assert()
+            .code(if satisfied { 0 } else { 1 });
+
+        let verdict: serde_json::Value =
+            serde_json::from_slice(&assert.get_output().stdout).unwrap();
+        assert_eq!(verdict["satisfied"], satisfied);
+        assert_eq!(verdict["found"], "3.11.0");
+        assert_eq!(verdict["required"], required);
+    }
+}
+
+#[test]
+fn warn_missing_dirs_reports_nonexistent_entries() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let missing = path_dir.path().join("gone");
+    let path = std::env::join_paths([path_dir.path(), missing.as_path()]).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", path)
+        .args(["--no-cache", "--warn-missing-dirs", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).into_owned();
+    assert!(stderr.contains(&missing.display().to_string()));
+    assert!(!stderr.contains(&format!("{}\n", path_dir.path().display())));
+}
+
+#[test]
+fn searches_every_path_entry() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let newer = write_script(second_dir.path(), "tool", "echo 'tool 1.1.0'");
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", path)
+        .args(["--no-cache", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", newer.display()));
+}
+
+#[test]
+fn all_lists_every_version() {
+    let old_dir = tempfile::tempdir().unwrap();
+    let new_dir = tempfile::tempdir().unwrap();
+    let old = write_script(old_dir.path(), "tool", "echo 'tool 1.9.0'");
+    let new = write_script(new_dir.path(), "tool", "echo 'tool 1.10.0'");
+    let path = std::env::join_paths([old_dir.path(), new_dir.path()]).unwrap();
+
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", path)
+        .args(["--no-cache", "--all", "tool"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\t1.10.0\n{}\t1.9.0\n",
+            new.display(),
+            old.display()
+        ));
+}
+
+#[test]
+fn json_prints_selected_and_all_executables() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 4.2.1'");
+
+    let assert = latest_version(path_dir.path())
+        .args(["--json", "tool"])
+        .assert()
+        .success();
+    let info: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(info["path"], tool.to_str().unwrap());
+    assert_eq!(info["version"], "4.2.1");
+
+    let assert = latest_version(path_dir.path())
+        .args(["--all", "--json", "tool"])
+        .assert()
+        .success();
+    let all: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(all.as_array().unwrap().len(), 1);
+    assert_eq!(all[0]["path"], tool.to_str().unwrap());
+    assert_eq!(all[0]["version"], "4.2.1");
+}
+
+#[test]
+fn json_errors_go_to_stderr() {
+    let path_dir = tempfile::tempdir().unwrap();
+
+    latest_version(path_dir.path())
+        .args(["--json", "no-such-tool"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn flag_overrides_default_version_flags() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(
+        path_dir.path(),
+        "tool",
+        r#"[ "$1" = "--print-version" ] && echo 'tool 2.5.0'"#,
+    );
+
+    latest_version(path_dir.path())
+        .arg("tool")
+        .assert()
+        .failure();
+
+    latest_version(path_dir.path())
+        .args(["--flag", "--print-version", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+}
+
+#[test]
+fn timeout_kills_hanging_probe() {
+    let path_dir = tempfile::tempdir().unwrap();
+    // Busy-wait with builtins only, since PATH holds nothing but the script.
+    write_script(path_dir.path(), "tool", "while :; do :; done");
+
+    let started = std::time::Instant::now();
+    latest_version(path_dir.path())
+        .args(["--timeout", "200", "tool"])
+        .assert()
+        .failure()
+        .stdout("");
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[test]
+fn env_vars_set_the_default_timeout_and_flags() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(
+        path_dir.path(),
+        "tool",
+        "case \"$1\" in -V) echo 'tool 2.3.4' ;; --version) while :; do :; done ;; esac",
+    );
+    let run = |env: &[(&str, &str)], args: &[&str]| {
+        let mut cmd = latest_version(path_dir.path());
+        cmd.envs(env.iter().copied()).args(args).arg("tool");
+        cmd.assert()
+    };
+
+    let started = std::time::Instant::now();
+    run(&[("LATEST_VERSION_TIMEOUT_MS", "200")], &[])
+        .failure()
+        .stdout("");
+    // Well short of the built-in five seconds.
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+
+    run(&[("LATEST_VERSION_FLAGS", "-x, -V")], &[])
+        .success()
+        .stdout(format!("{}\n", path_dir.path().join("tool").display()));
+
+    // An explicit flag or timeout wins over the environment.
+    run(
+        &[
+            ("LATEST_VERSION_FLAGS", "-V"),
+            ("LATEST_VERSION_TIMEOUT_MS", "60000"),
+        ],
+        &["--flag", "--version", "--timeout", "200"],
+    )
+    .failure()
+    .stdout("");
+}
+
+#[test]
+fn cache_is_used_only_when_asked_for() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let log = path_dir.path().join("probes.log");
+    write_script(
+        path_dir.path(),
+        "tool",
+        &format!("echo run >> '{}'\necho 'tool 1.2.3'", log.display()),
+    );
+    let run = |args: &[&str]| {
+        Command::cargo_bin("latest-version")
+            .unwrap()
+            .env("PATH", path_dir.path())
+            .env("HOME", cache_dir.path())
+            .env("XDG_CACHE_HOME", cache_dir.path())
+            .args(args)
+            .arg("tool")
+            .assert()
+            .success();
+    };
+    let probes = || std::fs::read_to_string(&log).unwrap().lines().count();
+
+    run(&[]);
+    run(&[]);
+    assert_eq!(probes(), 2);
+
+    run(&["--cache"]);
+    run(&["--cache"]);
+    assert_eq!(probes(), 3);
+
+    run(&["--cache", "--no-cache"]);
+    assert_eq!(probes(), 4);
+}
+
+#[test]
+fn min_and_require_filter_candidates() {
+    let old_dir = tempfile::tempdir().unwrap();
+    let new_dir = tempfile::tempdir().unwrap();
+    let old = write_script(old_dir.path(), "tool", "echo 'tool 3.8.10'");
+    let new = write_script(new_dir.path(), "tool", "echo 'tool 3.11.2'");
+    let path = std::env::join_paths([old_dir.path(), new_dir.path()]).unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", &path).arg("--no-cache").args(args);
+        cmd.assert()
+    };
+
+    run(&["--min", "3.10", "tool"])
+        .success()
+        .stdout(format!("{}\n", new.display()));
+    run(&["--require", "<3.10", "tool"])
+        .success()
+        .stdout(format!("{}\n", old.display()));
+    run(&["--require", "^3", "--all", "tool"])
+        .success()
+        .stdout(format!(
+            "{}\t3.11.2\n{}\t3.8.10\n",
+            new.display(),
+            old.display()
+        ));
+    run(&["--require", ">=4", "tool"])
+        .code(1)
+        .stdout("")
+        .stderr("Error [no-matching-version]: No version of tool matches >=4\n");
+}
+
+#[test]
+fn stable_only_also_filters_the_listing() {
+    let rc_dir = tempfile::tempdir().unwrap();
+    let stable_dir = tempfile::tempdir().unwrap();
+    write_script(rc_dir.path(), "tool", "echo 'tool 2.0.0-rc1'");
+    let stable = write_script(stable_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let path = std::env::join_paths([rc_dir.path(), stable_dir.path()]).unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", &path).arg("--stable-only").args(args);
+        cmd.assert()
+    };
+
+    run(&["tool"])
+        .success()
+        .stdout(format!("{}\n", stable.display()));
+    run(&["--all", "tool"])
+        .success()
+        .stdout(format!("{}\t1.0.0\n", stable.display()));
+    run(&["--count", "tool"]).success().stdout("1\n");
+}
+
+#[test]
+fn oldest_flips_the_selection() {
+    let old_dir = tempfile::tempdir().unwrap();
+    let new_dir = tempfile::tempdir().unwrap();
+    let old = write_script(old_dir.path(), "tool", "echo 'tool 1.4.0'");
+    write_script(new_dir.path(), "tool", "echo 'tool 2.0.1'");
+    let path = std::env::join_paths([new_dir.path(), old_dir.path()]).unwrap();
+
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", path)
+        .args(["--no-cache", "--oldest", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", old.display()));
+}
+
+#[test]
+fn show_version_and_version_only() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 3.2.1'");
+
+    latest_version(path_dir.path())
+        .args(["--show-version", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\t3.2.1\n", tool.display()));
+
+    latest_version(path_dir.path())
+        .args(["--version-only", "tool"])
+        .assert()
+        .success()
+        .stdout("3.2.1\n");
+
+    latest_version(path_dir.path())
+        .arg("tool")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+}
+
+#[test]
+fn format_fills_in_a_template() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 3.2.1'");
+    let run = |template: &str| {
+        latest_version(path_dir.path())
+            .args(["--format", template, "tool"])
+            .assert()
+    };
+
+    run("{version}\\t{path}")
+        .success()
+        .stdout(format!("3.2.1\t{}\n", tool.display()));
+    run("{{{major}.{minor}}} patch {patch} in {dir}")
+        .success()
+        .stdout(format!(
+            "{{3.2}} patch 1 in {}\n",
+            path_dir.path().display()
+        ));
+    latest_version(path_dir.path())
+        .args(["--all", "--format", "{major}:{path}", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("3:{}\n", tool.display()));
+
+    for (template, message) in [
+        ("{path} {name}", "unknown placeholder {name}"),
+        ("{path", "unclosed {path"),
+        ("v}", "unmatched }"),
+    ] {
+        let assert = run(template).code(2).stdout("");
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+        assert!(stderr.contains(message), "{}", stderr);
+    }
+}
+
+#[test]
+fn partial_versions_rank_a_bare_minor_below_an_explicit_patch() {
+    let short_dir = tempfile::tempdir().unwrap();
+    let full_dir = tempfile::tempdir().unwrap();
+    let short = write_script(short_dir.path(), "tool", "echo 'tool 3.11'");
+    let full = write_script(full_dir.path(), "tool", "echo 'tool 3.11.0'");
+    let path = std::env::join_paths([short_dir.path(), full_dir.path()]).unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", &path)
+            .args(["--no-cache", "--show-version"])
+            .args(args)
+            .arg("tool");
+        cmd.assert()
+    };
+
+    run(&[])
+        .success()
+        .stdout(format!("{}\t3.11.0\n", short.display()));
+    run(&["--partial-versions"])
+        .success()
+        .stdout(format!("{}\t3.11.0\n", full.display()));
+}
+
+#[test]
+fn unknown_scheme_lists_the_valid_ones() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 3.11'");
+
+    let assert = latest_version(path_dir.path())
+        .args(["--scheme", "semantic", "tool"])
+        .assert()
+        .code(2)
+        .stdout("");
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(
+        stderr.contains("[possible values: semver, lenient, calver, auto]"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn version_flag_reports_the_crate_version() {
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("latest-version {}\n", latest_version::VERSION));
+}
+
+#[test]
+fn semver_scheme_rejects_partial_versions() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 3.11'");
+
+    latest_version(path_dir.path())
+        .args(["--scheme", "semver", "tool"])
+        .assert()
+        .failure()
+        .stdout("");
+
+    latest_version(path_dir.path())
+        .args(["--scheme", "auto", "--version-only", "tool"])
+        .assert()
+        .success()
+        .stdout("3.11.0\n");
+}
+
+#[test]
+fn version_anchor_skips_decoy_numbers() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(
+        path_dir.path(),
+        "tool",
+        "echo 'Tool 2.0.1 (build 12345) release 3.4.1'",
+    );
+
+    latest_version(path_dir.path())
+        .args(["--version-anchor", "release", "--version-only", "tool"])
+        .assert()
+        .success()
+        .stdout("3.4.1\n");
+}
+
+#[test]
+fn verbose_logs_skipped_executables() {
+    let good_dir = tempfile::tempdir().unwrap();
+    let bad_dir = tempfile::tempdir().unwrap();
+    let good = write_script(good_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let bad = write_script(bad_dir.path(), "tool", "echo 'no numbers here'");
+    let path = std::env::join_paths([bad_dir.path(), good_dir.path()]).unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", &path)
+            .env_remove("RUST_LOG")
+            .arg("--no-cache")
+            .args(args)
+            .arg("tool");
+        let assert = cmd
+            .assert()
+            .success()
+            .stdout(format!("{}\n", good.display()));
+        String::from_utf8_lossy(&assert.get_output().stderr).into_owned()
+    };
+
+    assert_eq!(run(&[]), "");
+
+    let stderr = run(&["-v"]);
+    assert!(
+        stderr.contains(&format!("Skipping {}", bad.display())),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("Selected"), "{}", stderr);
+
+    let stderr = run(&["-vv"]);
+    assert!(stderr.contains("No version in the output of"), "{}", stderr);
+}
+
+#[test]
+fn multiple_commands_are_reported_independently() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 1.2.0'");
+    let other = write_script(path_dir.path(), "other", "echo 'other 3.4.5'");
+
+    let assert = latest_version(path_dir.path())
+        .args(["--show-version", "tool", "no-such-tool", "other"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\t1.2.0\n{}\t3.4.5\n",
+            tool.display(),
+            other.display()
+        ));
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(stderr.contains("no-such-tool"), "{}", stderr);
+
+    latest_version(path_dir.path())
+        .args(["no-such-tool", "nor-this-one"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn first_prints_earliest_match_without_probing() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    let log = first_dir.path().join("probes.log");
+    let probe = format!("echo run >> '{}'", log.display());
+    let first = write_script(
+        first_dir.path(),
+        "tool",
+        &format!("{}\necho 'tool 1.0.0'", probe),
+    );
+    write_script(
+        second_dir.path(),
+        "tool",
+        &format!("{}\necho 'tool 2.0.0'", probe),
+    );
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    for flag in ["--first", "--path-order"] {
+        Command::cargo_bin("latest-version")
+            .unwrap()
+            .env("PATH", &path)
+            .args(["--no-cache", flag, "tool"])
+            .assert()
+            .success()
+            .stdout(format!("{}\n", first.display()));
+    }
+
+    assert!(!log.exists());
+}
+
+#[test]
+fn exit_code_distinguishes_missing_from_unreadable() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "silent", "echo 'no version here'");
+    write_script(path_dir.path(), "tool", "echo 'tool 1.0.0'");
+
+    let assert = latest_version(path_dir.path())
+        .arg("no-such-tool")
+        .assert()
+        .code(127);
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stderr),
+        "Error [command-not-found]: Command not found: no-such-tool\n"
+    );
+
+    let assert = latest_version(path_dir.path())
+        .arg("silent")
+        .assert()
+        .code(3);
+    assert!(String::from_utf8_lossy(&assert.get_output().stderr)
+        .starts_with("Error [version-extraction-failed]: "));
+
+    latest_version(path_dir.path())
+        .args(["no-such-tool", "silent"])
+        .assert()
+        .code(1);
+
+    latest_version(path_dir.path())
+        .args(["no-such-tool", "silent", "tool"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn raw_prints_the_probe_output() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'Tool v1.2.3 (linux)'");
+
+    latest_version(path_dir.path())
+        .args(["--raw", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", tool.display()))
+        .stderr(format!(
+            "{} --version:\nTool v1.2.3 (linux)\n",
+            tool.display()
+        ));
+}
+
+#[test]
+fn raw_shows_the_output_the_version_was_read_from() {
+    let path_dir = tempfile::tempdir().unwrap();
+    let count = path_dir.path().join("count");
+    // Each run reports a newer build, so a second probe would show the wrong banner.
+    let tool = write_script(
+        path_dir.path(),
+        "tool",
+        &format!(
+            "n=0\n[ -f '{0}' ] && read n < '{0}'\nn=$((n + 1))\necho $n > '{0}'\necho \"Tool v1.2.$n\"",
+            count.display()
+        ),
+    );
+
+    for (args, expected) in [
+        (&["--raw"][..], "1.2.1"),
+        (&["--raw", "--min", "1.0"], "1.2.2"),
+    ] {
+        latest_version(path_dir.path())
+            .args(args)
+            .args(["--show-version", "tool"])
+            .assert()
+            .success()
+            .stdout(format!("{}\t{}\n", tool.display(), expected))
+            .stderr(format!(
+                "{} --version:\nTool v{}\n",
+                tool.display(),
+                expected
+            ));
+    }
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn check_outdated_compares_against_the_registry() {
+    use std::io::{BufRead, BufReader, Write};
+
+    // Answer each connection with the next body, then stop.
+    fn serve(bodies: &'static [&'static str]) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let reader = BufReader::new(stream.try_clone().unwrap());
+                for line in reader.lines() {
+                    if line.unwrap().is_empty() {
+                        break;
+                    }
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    let path_dir = tempfile::tempdir().unwrap();
+    let tool = write_script(path_dir.path(), "tool", "echo 'tool 1.4.0'");
+    let url = serve(&[r#"{"version": "1.5.0"}"#, r#"{"version": "1.4.0"}"#]);
+    let url = format!("{}/{{command}}", url);
+
+    latest_version(path_dir.path())
+        .args(["--check-outdated", &url, "tool"])
+        .assert()
+        .code(1)
+        .stdout(format!("{}\n", tool.display()))
+        .stderr(format!(
+            "{} (1.4.0) is behind the latest published 1.5.0\n",
+            tool.display()
+        ));
+
+    latest_version(path_dir.path())
+        .args(["--check-outdated", &url, "tool"])
+        .assert()
+        .success()
+        .stderr(format!(
+            "{} (1.4.0) is up to date with the latest published 1.4.0\n",
+            tool.display()
+        ));
+}
+
+#[test]
+fn regex_extracts_unusual_versions() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(
+        path_dir.path(),
+        "tool",
+        "echo 'Tool v2024w05 (build 0x1f3a)'",
+    );
+
+    latest_version(path_dir.path())
+        .args([
+            "--version-only",
+            "--regex",
+            r"v(?P<version>\d{4}w\d{2})",
+            "tool",
+        ])
+        .assert()
+        .success()
+        .stdout("2024w05\n");
+
+    latest_version(path_dir.path())
+        .args(["--regex", "(unclosed", "tool"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn exclude_dir_skips_directories_and_their_aliases() {
+    let keep_dir = tempfile::tempdir().unwrap();
+    let wrapper_dir = tempfile::tempdir().unwrap();
+    let alias_dir = tempfile::tempdir().unwrap();
+    let kept = write_script(keep_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let wrapper = write_script(wrapper_dir.path(), "tool", "echo 'tool 99.0.0'");
+    let alias = alias_dir.path().join("wrappers");
+    std::os::unix::fs::symlink(wrapper_dir.path(), &alias).unwrap();
+    let path =
+        std::env::join_paths([keep_dir.path(), wrapper_dir.path(), alias.as_path()]).unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", &path)
+            .arg("--no-cache")
+            .args(args)
+            .arg("tool");
+        cmd.assert()
+    };
+
+    run(&[])
+        .success()
+        .stdout(format!("{}\n", wrapper.display()));
+
+    let exclude = wrapper_dir.path().to_str().unwrap();
+    run(&["--exclude-dir", exclude])
+        .success()
+        .stdout(format!("{}\n", kept.display()));
+    run(&["--all", "--exclude-dir", exclude])
+        .success()
+        .stdout(format!("{}\t1.0.0\n", kept.display()));
+    run(&[
+        "--first",
+        "--exclude-dir",
+        keep_dir.path().to_str().unwrap(),
+        "--exclude-dir",
+        exclude,
+    ])
+    .code(127);
+}
+
+#[test]
+fn broken_path_entries_are_skipped_with_a_debug_log() {
+    let broken_dir = tempfile::tempdir().unwrap();
+    let empty_dir = tempfile::tempdir().unwrap();
+    let good_dir = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(
+        broken_dir.path().join("missing-target"),
+        broken_dir.path().join("tool"),
+    )
+    .unwrap();
+    let empty = empty_dir.path().join("tool");
+    std::fs::write(&empty, "").unwrap();
+    std::fs::set_permissions(&empty, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let good = write_script(good_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let path =
+        std::env::join_paths([broken_dir.path(), empty_dir.path(), good_dir.path()]).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .env_remove("RUST_LOG")
+        .args(["--no-cache", "-vv", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", good.display()));
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.contains("Skipping dangling symlink"), "{}", stderr);
+    assert!(
+        stderr.contains(&format!("Skipping empty file {}", empty.display())),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn recursive_finds_nested_installs() {
+    let root = tempfile::tempdir().unwrap();
+    let old_dir = root.path().join("1.4.0/bin");
+    let new_dir = root.path().join("2.0.0/bin");
+    std::fs::create_dir_all(&old_dir).unwrap();
+    std::fs::create_dir_all(&new_dir).unwrap();
+    write_script(&old_dir, "tool", "echo 'tool 1.4.0'");
+    let newest = write_script(&new_dir, "tool", "echo 'tool 2.0.0'");
+
+    latest_version(root.path()).arg("tool").assert().code(127);
+
+    latest_version(root.path())
+        .args(["--recursive", "2", "--show-version", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\t2.0.0\n", newest.display()));
+}
+
+#[test]
+fn compare_active_reports_the_first_on_path() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    let active = write_script(first_dir.path(), "tool", "echo 'tool 1.2.0'");
+    let newest = write_script(second_dir.path(), "tool", "echo 'tool 1.3.0'");
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .args(["--no-cache", "--compare-active", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", newest.display()));
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stderr),
+        format!(
+            "{} (1.2.0) is active instead of {} (1.3.0)\n",
+            active.display(),
+            newest.display()
+        )
+    );
+
+    let assert = latest_version(second_dir.path())
+        .args(["--compare-active", "tool"])
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stderr),
+        format!("{} (1.3.0) is the active executable\n", newest.display())
+    );
+}
+
+#[test]
+fn verbose_warns_about_tied_versions() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    let first = write_script(first_dir.path(), "tool", "echo 'tool 3.11.0'");
+    let second = write_script(second_dir.path(), "tool", "echo 'tool 3.11.0'");
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .env_remove("RUST_LOG")
+        .args(["--no-cache", "-v", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", first.display()));
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(
+        stderr.contains(&format!(
+            "3.11.0 is reported by more than one executable: {}, {}",
+            first.display(),
+            second.display()
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn unset_path_reports_command_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let tool = write_script(dir.path(), "tool", "echo 'tool 1.0.0'");
+    let run = |command: &str| {
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env_remove("PATH").args(["--no-cache", command]);
+        cmd.assert()
+    };
+
+    let assert = run("tool").code(127).stdout("");
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(!stderr.contains("PATH"), "{}", stderr);
+
+    run(tool.to_str().unwrap())
+        .success()
+        .stdout(format!("{}\n", tool.display()));
+}
+
+#[test]
+fn relative_path_entries_never_run_from_the_cwd() {
+    let dir = tempfile::tempdir().unwrap();
+    write_script(dir.path(), "tool", "echo 'tool 9.9.9'");
+
+    for path in [".:", ""] {
+        Command::cargo_bin("latest-version")
+            .unwrap()
+            .current_dir(dir.path())
+            .env("PATH", path)
+            .args(["--show-version", "tool"])
+            .assert()
+            .code(127)
+            .stdout("");
+    }
+}
+
+#[test]
+fn installed_exits_like_a_test_predicate() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "tool", "echo 'tool 3.11.4'");
+    let installed = |req: &str, command: &str| {
+        latest_version(path_dir.path())
+            .args(["--installed", req, command])
+            .assert()
+            .stdout("")
+    };
+
+    installed("~3.11", "tool").success().stderr("");
+    installed(">=3.12", "tool").code(1).stderr("");
+    let assert = installed("~3.11", "missing").code(127);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(
+        stderr.starts_with("Error [command-not-found]"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn count_reports_executables_and_distinct_versions() {
+    let dirs: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+    write_script(dirs[0].path(), "tool", "echo 'tool 2.0'");
+    write_script(dirs[1].path(), "tool", "echo 'tool 2.0.0'");
+    write_script(dirs[2].path(), "tool", "echo 'tool 3.1.0'");
+    let count = |dirs: &[tempfile::TempDir], args: &[&str], command: &str| {
+        let path = std::env::join_paths(dirs.iter().map(|dir| dir.path())).unwrap();
+        let mut cmd = Command::cargo_bin("latest-version").unwrap();
+        cmd.env("PATH", path)
+            .arg("--no-cache")
+            .args(args)
+            .arg(command);
+        let assert = cmd.assert().success();
+        String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
+    };
+
+    assert_eq!(count(&dirs, &["--count"], "missing"), "0\n");
+    assert_eq!(count(&dirs[..1], &["--count"], "tool"), "1\n");
+    assert_eq!(count(&dirs[..1], &["--count", "--distinct"], "tool"), "1\n");
+    assert_eq!(count(&dirs, &["--count"], "tool"), "3\n");
+    assert_eq!(count(&dirs, &["--count", "--distinct"], "tool"), "2\n");
+}
+
+#[test]
+fn no_color_emits_no_ansi_codes() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+    let new = write_script(second_dir.path(), "tool", "echo 'tool 2.0.0'");
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    for args in [&["--all"][..], &["--show-version"][..]] {
+        let assert = Command::cargo_bin("latest-version")
+            .unwrap()
+            .env("PATH", &path)
+            .env("NO_COLOR", "1")
+            .arg("--no-cache")
+            .args(args)
+            .arg("tool")
+            .assert()
+            .success();
+        let output = assert.get_output();
+        assert!(!output.stdout.contains(&b'\x1b'), "{:?}", output);
+        assert!(!output.stderr.contains(&b'\x1b'), "{:?}", output);
+    }
+
+    latest_version(second_dir.path())
+        .args(["--no-color", "--show-version", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\t2.0.0\n", new.display()));
+}
+
+#[test]
+fn all_paths_lists_every_match_in_path_order() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    let first = write_script(first_dir.path(), "tool", "echo 'tool 1.0.0'");
+    // Listed even though its version can't be read.
+    let second = write_script(second_dir.path(), "tool", "echo 'no version here'");
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .args(["--no-cache", "--all-paths", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n{}\n", first.display(), second.display()));
+
+    latest_version(first_dir.path())
+        .args(["--all-paths", "missing"])
+        .assert()
+        .code(127)
+        .stdout("");
+}
+
+#[test]
+fn verbose_all_explains_failed_probes() {
+    let good_dir = tempfile::tempdir().unwrap();
+    let bad_dir = tempfile::tempdir().unwrap();
+    let good = write_script(good_dir.path(), "tool", "echo 'tool 1.4.0'");
+    let bad = write_script(bad_dir.path(), "tool", "echo 'no version here'");
+    let path = std::env::join_paths([good_dir.path(), bad_dir.path()]).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .args(["--no-cache", "--all", "-v", "tool"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", stdout);
+    assert_eq!(lines[0], format!("{}\t1.4.0", good.display()));
+    assert!(
+        lines[1].starts_with(&format!(
+            "{}\tError [no-version-in-output]: ",
+            bad.display()
+        )),
+        "{}",
+        stdout
+    );
+
+    // Without --verbose, only the successes are listed.
+    Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .args(["--no-cache", "--all", "tool"])
+        .assert()
+        .success()
+        .stdout(format!("{}\t1.4.0\n", good.display()));
+}
+
+#[test]
+fn prefer_dir_breaks_version_ties() {
+    let system_dir = tempfile::tempdir().unwrap();
+    let tools_dir = tempfile::tempdir().unwrap();
+    let system = write_script(system_dir.path(), "tool", "echo 'tool 1.5.0'");
+    let tools = write_script(tools_dir.path(), "tool", "echo 'tool 1.5.0'");
+    let path = std::env::join_paths([system_dir.path(), tools_dir.path()]).unwrap();
+    let run = |args: &[&str], expected: &Path| {
+        Command::cargo_bin("latest-version")
+            .unwrap()
+            .env("PATH", &path)
+            .arg("--no-cache")
+            .args(args)
+            .arg("tool")
+            .assert()
+            .success()
+            .stdout(format!("{}\n", expected.display()));
+    };
+
+    run(&[], &system);
+    run(
+        &["--prefer-dir", tools_dir.path().to_str().unwrap()],
+        &tools,
+    );
+    // Filtering by version first still leaves the tie to --prefer-dir.
+    run(
+        &[
+            "--prefer-dir",
+            tools_dir.path().to_str().unwrap(),
+            "--min",
+            "1.0",
+        ],
+        &tools,
+    );
+    run(&["--require", "^1"], &system);
+}
+
+#[test]
+fn jsonl_prints_one_object_per_executable() {
+    let dirs: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+    let mut expected = Vec::new();
+    for (dir, version) in dirs.iter().zip(["1.0.0", "1.1.0", "2.0.0"]) {
+        let tool = write_script(dir.path(), "tool", &format!("echo 'tool {}'", version));
+        expected.push((tool.display().to_string(), version.to_string()));
+    }
+    let path = std::env::join_paths(dirs.iter().map(|dir| dir.path())).unwrap();
+
+    let assert = Command::cargo_bin("latest-version")
+        .unwrap()
+        .env("PATH", &path)
+        .args(["--no-cache", "--jsonl", "tool"])
+        .assert()
+        .success();
+
+    let mut found: Vec<(String, String)> = String::from_utf8_lossy(&assert.get_output().stdout)
+        .lines()
+        .map(|line| {
+            let info: serde_json::Value = serde_json::from_str(line).unwrap();
+            (
+                info["path"].as_str().unwrap().to_string(),
+                info["version"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    // Lines arrive as probes finish, so in no particular order.
+    found.sort();
+    expected.sort();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn dry_run_lists_probes_without_running_them() {
+    let first_dir = tempfile::tempdir().unwrap();
+    let second_dir = tempfile::tempdir().unwrap();
+    let marker = first_dir.path().join("ran");
+    let body = format!(": > '{}'\necho 'tool 1.0.0'", marker.display());
+    let first = write_script(first_dir.path(), "tool", &body);
+    let second = write_script(second_dir.path(), "tool", &body);
+    let path = std::env::join_paths([first_dir.path(), second_dir.path()]).unwrap();
+    let run = |args: &[&str]| {
+        let assert = Command::cargo_bin("latest-version")
+            .unwrap()
+            .env("PATH", &path)
+            .arg("--no-cache")
+            .args(args)
+            .arg("tool")
+            .assert()
+            .success();
+        String::from_utf8_lossy(&assert.get_output().stdout).into_owned()
+    };
+
+    let plan = run(&["--dry-run", "--probe", "version --short", "--probe", "-V"]);
+    assert!(!marker.exists(), "a probe was run");
+    assert_eq!(
+        plan,
+        format!(
+            "{first}\n  {first} version --short\n  {first} -V\n\
+             {second}\n  {second} version --short\n  {second} -V\n",
+            first = first.display(),
+            second = second.display()
+        )
+    );
+
+    // The same executables, in the same order, as discovery finds.
+    let planned: Vec<&str> = plan.lines().filter(|line| !line.starts_with(' ')).collect();
+    assert_eq!(planned.join("\n") + "\n", run(&["--all-paths"]));
+
+    let plan = run(&["--dry-run"]);
+    assert!(plan.contains(&format!("  {} --version\n", first.display())));
+    assert!(!marker.exists(), "a probe was run");
+}
+
+#[test]
+fn glob_selects_the_newest_versioned_name() {
+    let path_dir = tempfile::tempdir().unwrap();
+    write_script(path_dir.path(), "python3.10", "echo 'Python 3.10.13'");
+    let newest = write_script(path_dir.path(), "python3.11", "echo 'Python 3.11.7'");
+
+    latest_version(path_dir.path())
+        .arg("python")
+        .assert()
+        .code(127);
+
+    latest_version(path_dir.path())
+        .args(["--glob", "--show-version", "python*"])
+        .assert()
+        .success()
+        .stdout(format!("{}\t3.11.7\n", newest.display()));
+}
@@ -1,10 +1,18 @@
+import asyncio
+import os
 import sys
 import pytest
+import latest_version
 from latest_version import (
     find_latest_command,
+    find_latest_command_detailed,
+    find_all_versions,
     find_executables,
     get_version,
     ExecutableInfo,
+    LatestVersionError,
+    CommandNotFoundError,
+    VersionExtractionError,
 )
 
 
@@ -67,4 +75,118 @@ def test_find_latest_command():
         print(f"Version: {info.version}")
     except Exception as e:
         print(f"Test skipped: {e}")
-        pytest.skip("Command not available")
\ No newline at end of file
+        pytest.skip("Command not available")
+
+
+def write_script(directory, name, body):
+    path = directory / name
+    path.write_text("#!/bin/sh\n" + body + "\n")
+    path.chmod(0o755)
+    return str(path)
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="uses shell script stubs")
+def test_find_latest_command_detailed_reports_failures(tmp_path, monkeypatch):
+    bad_dir = tmp_path / "bad"
+    good_dir = tmp_path / "good"
+    bad_dir.mkdir()
+    good_dir.mkdir()
+    bad = write_script(bad_dir, "lvtool", "echo 'no version here'")
+    write_script(good_dir, "lvtool", "echo 'lvtool 1.2.3'")
+    monkeypatch.setenv("PATH", os.pathsep.join([str(bad_dir), str(good_dir)]))
+
+    report = find_latest_command_detailed("lvtool")
+
+    assert any(path == bad for path, _ in report.failures)
+    assert all(isinstance(message, str) for _, message in report.failures)
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="uses shell script stubs")
+def test_find_all_versions(tmp_path, monkeypatch):
+    old_dir = tmp_path / "old"
+    new_dir = tmp_path / "new"
+    old_dir.mkdir()
+    new_dir.mkdir()
+    old = write_script(old_dir, "lvtool", "echo 'lvtool 1.2.3'")
+    new = write_script(new_dir, "lvtool", "echo 'lvtool 1.10.0'")
+    monkeypatch.setenv("PATH", os.pathsep.join([str(old_dir), str(new_dir)]))
+
+    infos = find_all_versions("lvtool")
+
+    assert all(isinstance(info, ExecutableInfo) for info in infos)
+    assert [(info.path, info.version) for info in infos] == [
+        (old, "1.2.3"),
+        (new, "1.10.0"),
+    ]
+    assert repr(infos[0]) == f'ExecutableInfo(path="{old}", version="1.2.3")'
+
+
+def test_executable_info_sorts_by_version():
+    infos = [
+        ExecutableInfo("/opt/b/python3", "3.10.2"),
+        ExecutableInfo("/usr/bin/python3", "3.9.18"),
+        ExecutableInfo("/opt/a/python3", "3.10.2"),
+        ExecutableInfo("/usr/local/bin/python3", "3.12.0"),
+    ]
+
+    assert [(info.version, info.path) for info in sorted(infos)] == [
+        ("3.9.18", "/usr/bin/python3"),
+        ("3.10.2", "/opt/a/python3"),
+        ("3.10.2", "/opt/b/python3"),
+        ("3.12.0", "/usr/local/bin/python3"),
+    ]
+    assert max(infos).version == "3.12.0"
+    assert infos[1] < infos[0]
+
+    assert ExecutableInfo("/usr/bin/python3", "3.9.18") == infos[1]
+    assert infos[0] != infos[2]
+    assert infos[0] != "3.10.2"
+    assert str(infos[1]) == "/usr/bin/python3 (3.9.18)"
+
+
+def test_executable_info_is_hashable():
+    info = ExecutableInfo("/usr/bin/python3", "3.9.18")
+    same = ExecutableInfo("/usr/bin/python3", "3.9.18")
+
+    assert hash(info) == hash(same)
+    assert {info, same, ExecutableInfo("/usr/bin/python3", "3.12.0")} == {
+        info,
+        ExecutableInfo("/usr/bin/python3", "3.12.0"),
+    }
+    assert {info: "system"}[same] == "system"
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="uses shell script stubs")
+def test_errors_raise_typed_exceptions(tmp_path, monkeypatch):
+    silent = write_script(tmp_path, "lvsilent", "echo 'no version here'")
+    monkeypatch.setenv("PATH", str(tmp_path))
+
+    with pytest.raises(CommandNotFoundError) as missing:
+        find_latest_command("lv-no-such-tool")
+    assert isinstance(missing.value, LatestVersionError)
+    assert isinstance(missing.value, RuntimeError)
+    assert "lv-no-such-tool" in str(missing.value)
+
+    with pytest.raises(VersionExtractionError):
+        get_version(silent)
+
+    with pytest.raises(LatestVersionError):
+        find_latest_command("lvsilent")
+
+
+@pytest.mark.skipif(
+    not hasattr(latest_version, "find_latest_command_async"),
+    reason="built without the tokio feature",
+)
+@pytest.mark.skipif(sys.platform == "win32", reason="uses shell script stubs")
+def test_find_latest_command_async(tmp_path, monkeypatch):
+    write_script(tmp_path, "lvtool", "echo 'lvtool 2.4.6'")
+    monkeypatch.setenv("PATH", str(tmp_path))
+
+    async def probe():
+        return await latest_version.find_latest_command_async("lvtool")
+
+    info = asyncio.run(probe())
+
+    assert isinstance(info, ExecutableInfo)
+    assert info.version == "2.4.6"